@@ -17,10 +17,11 @@
 use std::iter;
 use std::path::Path;
 
-use criterion::Criterion;
+use criterion::{Benchmark, Criterion, ParameterizedBenchmark};
 use serde::*;
 
 use kaosu_packer::geom::Cuboid;
+use kaosu_packer::instances::generate_mpv;
 use kaosu_packer::*;
 
 fn main() {
@@ -28,6 +29,10 @@ fn main() {
     pack_easy(&mut criterion);
     pack_medium(&mut criterion);
     pack_hard(&mut criterion);
+    greedy_large(&mut criterion);
+    pack_large(&mut criterion);
+    pack_mpv(&mut criterion);
+    pack_threads(&mut criterion);
 }
 
 fn pack_easy(c: &mut Criterion) {
@@ -63,6 +68,68 @@ fn pack_hard(c: &mut Criterion) {
     });
 }
 
+fn greedy_large(c: &mut Criterion) {
+    let items = load_items("testdata/large.csv");
+    let params = Params::default();
+    let bin = Cuboid::new(100, 100, 100);
+    c.bench_function("greedy_large", move |b| {
+        b.iter(|| {
+            pack_boxes_greedy(params, bin, &items);
+        })
+    });
+}
+
+fn pack_large(c: &mut Criterion) {
+    let items = load_items("testdata/large.csv");
+    let params = Params {
+        population_factor: 1,
+        max_generations: 5,
+        ..Params::default()
+    };
+    let bin = Cuboid::new(100, 100, 100);
+    let benchmark = Benchmark::new("pack_large", move |b| {
+        b.iter(|| {
+            pack_boxes(params, bin, &items);
+        })
+    });
+    c.bench("pack_large", benchmark.sample_size(10));
+}
+
+fn pack_mpv(c: &mut Criterion) {
+    let instances: Vec<_> = (1..=8).map(|class| generate_mpv(class, 50, 2019)).collect();
+    let benchmark = ParameterizedBenchmark::new(
+        "pack_mpv",
+        move |b, &class| {
+            let instance = &instances[class as usize - 1];
+            let params = Params::default();
+            b.iter(|| {
+                pack_boxes(params, instance.bin, &instance.boxes);
+            })
+        },
+        1..=8u32,
+    );
+    c.bench("pack_mpv", benchmark.sample_size(10));
+}
+
+fn pack_threads(c: &mut Criterion) {
+    let items = load_items("testdata/medium.csv");
+    let bin = Cuboid::new(100, 100, 100);
+    let benchmark = ParameterizedBenchmark::new(
+        "pack_threads",
+        move |b, &threads| {
+            let params = Params {
+                threads,
+                ..Params::default()
+            };
+            b.iter(|| {
+                pack_boxes(params, bin, &items);
+            })
+        },
+        vec![1, 2, 4],
+    );
+    c.bench("pack_threads", benchmark.sample_size(10));
+}
+
 #[derive(Debug, Deserialize)]
 struct Record {
     width: i32,
@@ -76,9 +143,10 @@ fn load_items<P: AsRef<Path>>(path: P) -> Vec<Cuboid> {
     let mut v = Vec::new();
     for record in rdr.deserialize() {
         let record: Record = record.unwrap();
-        v.extend(
-            iter::repeat(Cuboid::new(record.width, record.depth, record.height)).take(record.count),
-        );
+        v.extend(iter::repeat_n(
+            Cuboid::new(record.width, record.depth, record.height),
+            record.count,
+        ));
     }
     v
 }
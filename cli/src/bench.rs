@@ -0,0 +1,416 @@
+/*
+ * Copyright 2019 Zejun Li
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Compares parameter configurations on a directory of instances.
+//!
+//! Every configuration packs every instance once per seed. The bins, the utilization and
+//! the time of the runs are summarized per instance and configuration, and every two
+//! configurations are compared with the Wilcoxon signed-rank test over the runs with the same
+//! instance and seed.
+
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use clap::{value_t, App, Arg, ArgMatches, SubCommand};
+use serde::*;
+
+use kaosu_packer::geom::Cuboid;
+use kaosu_packer::json::{Item, Problem};
+use kaosu_packer::{pack_boxes_with_report, Params};
+
+use super::pack::{check_fit, load_items, parse_bin};
+use super::params;
+use super::wilcoxon::signed_rank;
+
+pub fn command() -> App<'static, 'static> {
+    SubCommand::with_name("bench")
+        .about("Runs parameter configurations over instances and compares the solutions")
+        .arg(
+            Arg::with_name("dir")
+                .required(true)
+                .help("A directory of JSON problems and CSV item files"),
+        )
+        .arg(
+            Arg::with_name("bin")
+                .long("bin")
+                .takes_value(true)
+                .value_name("WxDxH")
+                .help("The size of the bins of the CSV item files"),
+        )
+        .arg(
+            Arg::with_name("config")
+                .long("config")
+                .takes_value(true)
+                .value_name("FILE")
+                .multiple(true)
+                .number_of_values(1)
+                .help("A .toml or .json parameter file, may be repeated, the default parameters without"),
+        )
+        .arg(
+            Arg::with_name("seeds")
+                .long("seeds")
+                .takes_value(true)
+                .value_name("N")
+                .default_value("5")
+                .help("Runs of every configuration on every instance, seeded from --seed on"),
+        )
+        .arg(
+            Arg::with_name("csv")
+                .long("csv")
+                .takes_value(true)
+                .value_name("FILE")
+                .help("Writes the summary as CSV"),
+        )
+        .arg(
+            Arg::with_name("runs")
+                .long("runs")
+                .takes_value(true)
+                .value_name("FILE")
+                .help("Writes every run as CSV"),
+        )
+        .args(&params::args())
+}
+
+struct Instance {
+    name: String,
+    bin: Cuboid,
+    items: Vec<Item>,
+    params: Params,
+}
+
+struct Config {
+    name: String,
+    file: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+struct Run {
+    instance: String,
+    config: String,
+    seed: u64,
+    bins: usize,
+    utilization: f64,
+    time: f64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+struct Summary {
+    instance: String,
+    config: String,
+    runs: usize,
+    bins_mean: f64,
+    bins_std: f64,
+    bins_best: f64,
+    utilization_mean: f64,
+    utilization_std: f64,
+    utilization_best: f64,
+    time_mean: f64,
+    time_std: f64,
+    time_best: f64,
+}
+
+pub fn run(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let bin = match matches.value_of("bin") {
+        Some(spec) => Some(parse_bin(spec)?),
+        None => None,
+    };
+    let instances = load_instances(matches.value_of("dir").unwrap(), bin)?;
+    let configs: Vec<Config> = match matches.values_of("config") {
+        Some(files) => {
+            let files: Vec<&str> = files.collect();
+            if let Some(file) = files
+                .iter()
+                .enumerate()
+                .find_map(|(i, f)| files[..i].contains(f).then_some(f))
+            {
+                return Err(format!("config {} is given twice", file).into());
+            }
+            let paths: Vec<&Path> = files.iter().map(Path::new).collect();
+            names(&paths)
+                .into_iter()
+                .zip(files)
+                .map(|(name, file)| Config {
+                    name,
+                    file: Some(file.to_string()),
+                })
+                .collect()
+        }
+        None => vec![Config {
+            name: "default".to_string(),
+            file: None,
+        }],
+    };
+    let num_seeds = value_t!(matches, "seeds", u64)?;
+    let first_seed = params::apply(matches, Params::default())?.seed.unwrap_or(1);
+
+    let mut runs = Vec::new();
+    for instance in &instances {
+        for config in &configs {
+            let mut params = instance.params;
+            if let Some(file) = &config.file {
+                params = params::load(file, params)?;
+            }
+            params = params::apply(matches, params)?;
+            check_fit(&params, instance.bin, &instance.items)
+                .map_err(|e| format!("{}: {}", instance.name, e))?;
+            for seed in first_seed..first_seed + num_seeds {
+                params.seed = Some(seed);
+                let report = pack_boxes_with_report(params, instance.bin, &instance.items);
+                let run = Run {
+                    instance: instance.name.clone(),
+                    config: config.name.clone(),
+                    seed,
+                    bins: report.bins.len(),
+                    utilization: report.overall_utilization,
+                    time: report.elapsed.as_secs_f64(),
+                };
+                eprintln!(
+                    "{} {} seed {}: {} bins in {:.2}s",
+                    run.instance, run.config, seed, run.bins, run.time
+                );
+                runs.push(run);
+            }
+        }
+    }
+
+    if let Some(path) = matches.value_of("runs") {
+        let mut wtr = csv::Writer::from_path(path)?;
+        for run in &runs {
+            wtr.serialize(run)?;
+        }
+        wtr.flush()?;
+    }
+    let summaries = summarize(&instances, &configs, &runs);
+    if let Some(path) = matches.value_of("csv") {
+        let mut wtr = csv::Writer::from_path(path)?;
+        for summary in &summaries {
+            wtr.serialize(summary)?;
+        }
+        wtr.flush()?;
+    }
+    print_summaries(&summaries);
+    if configs.len() > 1 {
+        println!();
+        print_comparisons(&configs, &runs);
+    }
+    Ok(())
+}
+
+// the file stems of the paths, or the whole paths where stems clash.
+fn names(paths: &[&Path]) -> Vec<String> {
+    let stems: Vec<String> = paths
+        .iter()
+        .map(|p| {
+            p.file_stem().map_or_else(
+                || p.display().to_string(),
+                |s| s.to_string_lossy().into_owned(),
+            )
+        })
+        .collect();
+    stems
+        .iter()
+        .zip(paths)
+        .map(|(stem, path)| {
+            if stems.iter().filter(|s| *s == stem).count() > 1 {
+                path.display().to_string()
+            } else {
+                stem.clone()
+            }
+        })
+        .collect()
+}
+
+// JSON problems keep their parameters, which the configurations override.
+fn load_instances(dir: &str, bin: Option<Cuboid>) -> Result<Vec<Instance>, Box<dyn Error>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<_, _>>()?;
+    paths.retain(|p| matches!(p.extension().and_then(|e| e.to_str()), Some("json" | "csv")));
+    paths.sort();
+
+    let names = names(&paths.iter().map(PathBuf::as_path).collect::<Vec<_>>());
+    let mut instances = Vec::new();
+    for (path, name) in paths.iter().zip(names) {
+        let instance = match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => {
+                let problem = Problem::load(path)?;
+                Instance {
+                    name,
+                    bin: problem.bin,
+                    items: problem.items,
+                    params: problem.params,
+                }
+            }
+            Some("csv") => Instance {
+                bin: bin.ok_or_else(|| format!("--bin is needed for {}", path.display()))?,
+                items: load_items(path)?,
+                params: Params::default(),
+                name,
+            },
+            _ => continue,
+        };
+        instances.push(instance);
+    }
+    if instances.is_empty() {
+        return Err(format!("no .json or .csv instances in {}", dir).into());
+    }
+    Ok(instances)
+}
+
+// one row per instance and configuration, and per configuration a total over the instances
+// for every seed when there are several instances.
+fn summarize(instances: &[Instance], configs: &[Config], runs: &[Run]) -> Vec<Summary> {
+    let mut summaries = Vec::new();
+    for instance in instances {
+        for config in configs {
+            let runs: Vec<&Run> = runs
+                .iter()
+                .filter(|r| r.instance == instance.name && r.config == config.name)
+                .collect();
+            let bins: Vec<f64> = runs.iter().map(|r| r.bins as f64).collect();
+            let utilization: Vec<f64> = runs.iter().map(|r| r.utilization).collect();
+            let time: Vec<f64> = runs.iter().map(|r| r.time).collect();
+            summaries.push(summary(
+                &instance.name,
+                &config.name,
+                &bins,
+                &utilization,
+                &time,
+            ));
+        }
+    }
+
+    if instances.len() > 1 {
+        for config in configs {
+            let runs: Vec<&Run> = runs.iter().filter(|r| r.config == config.name).collect();
+            let mut seeds: Vec<u64> = runs.iter().map(|r| r.seed).collect();
+            seeds.sort();
+            seeds.dedup();
+            let (mut bins, mut utilization, mut time) = (Vec::new(), Vec::new(), Vec::new());
+            for seed in seeds {
+                let runs: Vec<&&Run> = runs.iter().filter(|r| r.seed == seed).collect();
+                bins.push(runs.iter().map(|r| r.bins as f64).sum());
+                utilization.push(mean(
+                    &runs.iter().map(|r| r.utilization).collect::<Vec<_>>(),
+                ));
+                time.push(runs.iter().map(|r| r.time).sum());
+            }
+            summaries.push(summary("total", &config.name, &bins, &utilization, &time));
+        }
+    }
+    summaries
+}
+
+fn summary(
+    instance: &str,
+    config: &str,
+    bins: &[f64],
+    utilization: &[f64],
+    time: &[f64],
+) -> Summary {
+    let min = |v: &[f64]| v.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = |v: &[f64]| v.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    Summary {
+        instance: instance.to_string(),
+        config: config.to_string(),
+        runs: bins.len(),
+        bins_mean: mean(bins),
+        bins_std: std_dev(bins),
+        bins_best: min(bins),
+        utilization_mean: mean(utilization),
+        utilization_std: std_dev(utilization),
+        utilization_best: max(utilization),
+        time_mean: mean(time),
+        time_std: std_dev(time),
+        time_best: min(time),
+    }
+}
+
+fn mean(v: &[f64]) -> f64 {
+    v.iter().sum::<f64>() / v.len().max(1) as f64
+}
+
+// the sample standard deviation.
+fn std_dev(v: &[f64]) -> f64 {
+    if v.len() < 2 {
+        return 0.;
+    }
+    let m = mean(v);
+    let sum: f64 = v.iter().map(|x| (x - m) * (x - m)).sum();
+    (sum / (v.len() - 1) as f64).sqrt()
+}
+
+fn print_summaries(summaries: &[Summary]) {
+    println!(
+        "| instance | config | runs | bins mean | bins std | bins best | utilization mean | utilization std | utilization best | time mean (s) | time std (s) | time best (s) |"
+    );
+    println!("|---|---|---:|---:|---:|---:|---:|---:|---:|---:|---:|---:|");
+    for s in summaries {
+        println!(
+            "| {} | {} | {} | {:.2} | {:.2} | {} | {:.2}% | {:.2}% | {:.2}% | {:.2} | {:.2} | {:.2} |",
+            s.instance,
+            s.config,
+            s.runs,
+            s.bins_mean,
+            s.bins_std,
+            s.bins_best,
+            s.utilization_mean * 100.,
+            s.utilization_std * 100.,
+            s.utilization_best * 100.,
+            s.time_mean,
+            s.time_std,
+            s.time_best
+        );
+    }
+}
+
+// pairs the runs of two configurations by instance and seed.
+fn print_comparisons(configs: &[Config], runs: &[Run]) {
+    println!("| config a | config b | pairs | a fewer bins | b fewer bins | p (bins) | p (utilization) |");
+    println!("|---|---|---:|---:|---:|---:|---:|");
+    for (i, a) in configs.iter().enumerate() {
+        for b in &configs[i + 1..] {
+            let pairs: Vec<(&Run, &Run)> = runs
+                .iter()
+                .filter(|r| r.config == a.name)
+                .filter_map(|ra| {
+                    runs.iter()
+                        .find(|rb| {
+                            rb.config == b.name && rb.instance == ra.instance && rb.seed == ra.seed
+                        })
+                        .map(|rb| (ra, rb))
+                })
+                .collect();
+            let bins_a: Vec<f64> = pairs.iter().map(|(ra, _)| ra.bins as f64).collect();
+            let bins_b: Vec<f64> = pairs.iter().map(|(_, rb)| rb.bins as f64).collect();
+            let util_a: Vec<f64> = pairs.iter().map(|(ra, _)| ra.utilization).collect();
+            let util_b: Vec<f64> = pairs.iter().map(|(_, rb)| rb.utilization).collect();
+            let a_fewer = pairs.iter().filter(|(ra, rb)| ra.bins < rb.bins).count();
+            let b_fewer = pairs.iter().filter(|(ra, rb)| rb.bins < ra.bins).count();
+            println!(
+                "| {} | {} | {} | {} | {} | {:.4} | {:.4} |",
+                a.name,
+                b.name,
+                pairs.len(),
+                a_fewer,
+                b_fewer,
+                signed_rank(&bins_a, &bins_b).p_value,
+                signed_rank(&util_a, &util_b).p_value
+            );
+        }
+    }
+}
@@ -0,0 +1,60 @@
+/*
+ * Copyright 2019 Zejun Li
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! The `kaosu` command.
+//!
+//! ```text
+//! kaosu pack --bin 100x100x100 --items items.csv --params params.toml --out solution.json
+//! kaosu verify problem.json solution.json
+//! kaosu stats solution.json --problem problem.json
+//! kaosu bench instances --bin 100x100x100 --config a.toml --config b.toml --seeds 10
+//! ```
+
+mod bench;
+mod pack;
+mod params;
+mod stats;
+mod verify;
+mod wilcoxon;
+
+use std::error::Error;
+use std::process;
+
+use clap::{crate_version, App, AppSettings};
+
+fn main() {
+    let matches = App::new("kaosu")
+        .version(crate_version!())
+        .about("Packs boxes into as few bins as possible")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(pack::command())
+        .subcommand(verify::command())
+        .subcommand(stats::command())
+        .subcommand(bench::command())
+        .get_matches();
+
+    let result: Result<(), Box<dyn Error>> = match matches.subcommand() {
+        ("pack", Some(m)) => pack::run(m),
+        ("verify", Some(m)) => verify::run(m),
+        ("stats", Some(m)) => stats::run(m),
+        ("bench", Some(m)) => bench::run(m),
+        _ => unreachable!(),
+    };
+    if let Err(err) = result {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,206 @@
+/*
+ * Copyright 2019 Zejun Li
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::error::Error;
+use std::path::Path;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde::*;
+
+use kaosu_packer::geom::Cuboid;
+use kaosu_packer::json::{Item, Problem, Solution};
+use kaosu_packer::{pack_boxes_with_report, FitError, IdentifiedPlacement, PackReport, Params};
+
+use super::params;
+
+pub fn command() -> App<'static, 'static> {
+    SubCommand::with_name("pack")
+        .about("Packs the items and prints a summary of the bins")
+        .arg(
+            Arg::with_name("bin")
+                .long("bin")
+                .takes_value(true)
+                .value_name("WxDxH")
+                .required_unless("problem")
+                .help("The size of the bins, e.g. 100x100x100"),
+        )
+        .arg(
+            Arg::with_name("items")
+                .long("items")
+                .takes_value(true)
+                .value_name("FILE")
+                .required_unless("problem")
+                .help("A CSV file with width, depth, height and count columns"),
+        )
+        .arg(
+            Arg::with_name("problem")
+                .long("problem")
+                .takes_value(true)
+                .value_name("FILE")
+                .conflicts_with_all(&["bin", "items"])
+                .help("A problem in the JSON format instead of --bin and --items"),
+        )
+        .arg(
+            Arg::with_name("out")
+                .long("out")
+                .takes_value(true)
+                .value_name("FILE")
+                .help("Writes the solution in the JSON format"),
+        )
+        .arg(
+            Arg::with_name("save-problem")
+                .long("save-problem")
+                .takes_value(true)
+                .value_name("FILE")
+                .help("Writes the problem in the JSON format, e.g. for kaosu verify"),
+        )
+        .arg(
+            Arg::with_name("params")
+                .long("params")
+                .takes_value(true)
+                .value_name("FILE")
+                .help("Reads the parameters from a .toml or .json file, flags take precedence"),
+        )
+        .args(&params::args())
+}
+
+pub fn run(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let mut problem = match matches.value_of("problem") {
+        Some(path) => Problem::load(path)?,
+        None => {
+            let bin = parse_bin(matches.value_of("bin").unwrap())?;
+            let items = load_items(matches.value_of("items").unwrap())?;
+            Problem::new(bin, items, Params::default())
+        }
+    };
+    if let Some(path) = matches.value_of("params") {
+        problem.params = params::load(path, problem.params)?;
+    }
+    problem.params = params::apply(matches, problem.params)?;
+    if let Some(path) = matches.value_of("save-problem") {
+        problem.save(path)?;
+    }
+
+    check_fit(&problem.params, problem.bin, &problem.items)?;
+    let report = pack_boxes_with_report(problem.params, problem.bin, &problem.items);
+    print_summary(&problem, &report);
+
+    if let Some(path) = matches.value_of("out") {
+        let bins: Vec<Vec<_>> = report
+            .bins
+            .iter()
+            .map(|bin| {
+                bin.iter()
+                    .map(|p| IdentifiedPlacement {
+                        space: p.space,
+                        id: problem.items[p.item_idx].id.clone(),
+                    })
+                    .collect()
+            })
+            .collect();
+        let rotation_type = problem.params.box_rotation_type;
+        Solution::new(problem.bin, rotation_type, &bins).save(path)?;
+    }
+    Ok(())
+}
+
+// fails naming the item which does not fit in the bin, where packing would panic.
+pub fn check_fit(params: &Params, bin: Cuboid, items: &[Item]) -> Result<(), Box<dyn Error>> {
+    match params.check_fit(bin, items) {
+        Err(FitError::Oversize { item_idx, cuboid }) => Err(format!(
+            "item {} ({}x{}x{}) does not fit in the bin with the clearance and margin",
+            items[item_idx].id, cuboid.width, cuboid.depth, cuboid.height
+        )
+        .into()),
+        result => Ok(result?),
+    }
+}
+
+pub fn parse_bin(spec: &str) -> Result<Cuboid, Box<dyn Error>> {
+    let sizes = spec
+        .split('x')
+        .map(|s| s.trim().parse::<i32>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| format!("invalid bin size: {}", spec))?;
+    match sizes[..] {
+        [width, depth, height] => Ok(Cuboid::new(width, depth, height)),
+        _ => Err(format!("invalid bin size: {}, expected WxDxH", spec).into()),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Record {
+    width: i32,
+    depth: i32,
+    height: i32,
+    count: usize,
+}
+
+// the items of the n-th row are named n-1, n-2, ...
+pub fn load_items<P: AsRef<Path>>(path: P) -> Result<Vec<Item>, Box<dyn Error>> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_path(path)?;
+    let mut items = Vec::new();
+    for (row, record) in rdr.deserialize().enumerate() {
+        let record: Record = record?;
+        for n in 1..=record.count {
+            items.push(Item {
+                id: format!("{}-{}", row + 1, n),
+                width: record.width,
+                depth: record.depth,
+                height: record.height,
+                attributes: Default::default(),
+            });
+        }
+    }
+    Ok(items)
+}
+
+fn print_summary(problem: &Problem, report: &PackReport) {
+    println!(
+        "{:>4} {:>6} {:>12} {:>12}",
+        "bin", "items", "volume", "utilization"
+    );
+    for (i, bin) in report.bins.iter().enumerate() {
+        let volume: i64 = bin.iter().map(|p| p.space.volume()).sum();
+        println!(
+            "{:>4} {:>6} {:>12} {:>11.2}%",
+            i + 1,
+            bin.len(),
+            volume,
+            report.utilization[i] * 100.
+        );
+    }
+    println!(
+        "{} items in {} bins of {}x{}x{}, lower bound {} (gap {:.2}%{}), overall utilization {:.2}%",
+        problem.items.len(),
+        report.bins.len(),
+        problem.bin.width,
+        problem.bin.depth,
+        problem.bin.height,
+        report.lower_bound,
+        report.gap * 100.,
+        if report.optimal { ", optimal" } else { "" },
+        report.overall_utilization * 100.
+    );
+    println!(
+        "{} generations, {} solutions decoded in {:.2}s",
+        report.generations,
+        report.num_decoded,
+        report.elapsed.as_secs_f64()
+    );
+}
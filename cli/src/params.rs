@@ -0,0 +1,208 @@
+/*
+ * Copyright 2019 Zejun Li
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Packer parameters from a TOML or JSON file, overridden by flags.
+//!
+//! The file holds the fields of `Params` and takes the time limit in seconds:
+//!
+//! ```toml
+//! box_rotation_type = "TwoDimension"
+//! time_limit = 2.5
+//!
+//! [objective]
+//! center_of_gravity = 0.5
+//! ```
+
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use clap::{value_t, Arg, ArgMatches};
+use kaosu_packer::geom::RotationType;
+use kaosu_packer::{Layout, Params};
+use serde_json::Value;
+
+pub fn args() -> Vec<Arg<'static, 'static>> {
+    let number = |name: &'static str, help: &'static str| {
+        Arg::with_name(name)
+            .long(name)
+            .takes_value(true)
+            .value_name("N")
+            .help(help)
+    };
+    vec![
+        number("population-factor", "Individuals per box in a generation"),
+        number(
+            "elites-percentage",
+            "Fraction of the population kept as elites",
+        ),
+        number(
+            "mutants-percentage",
+            "Fraction of the population replaced by mutants",
+        ),
+        number(
+            "inherit-elite-probability",
+            "Probability of a child taking a gene from its elite parent",
+        ),
+        number("max-generations", "Generations to evolve at most"),
+        number(
+            "max-generations-no-improvement",
+            "Stop after this many generations without a better solution",
+        ),
+        Arg::with_name("rotation")
+            .long("rotation")
+            .takes_value(true)
+            .possible_values(&["3d", "2d"])
+            .help("Turn the boxes every way, or only around the vertical axis"),
+        number(
+            "exact-threshold",
+            "Solve problems with at most this many boxes exactly first",
+        ),
+        number("num-bins-weight", "Objective weight of the number of bins"),
+        number(
+            "least-load-weight",
+            "Objective weight of the least loaded bin",
+        ),
+        number(
+            "center-of-gravity-weight",
+            "Objective weight of the height of the centres of gravity",
+        ),
+        number(
+            "orientation-changes-weight",
+            "Objective weight of the boxes turned from their orientation",
+        ),
+        Arg::with_name("layout")
+            .long("layout")
+            .takes_value(true)
+            .possible_values(&["fill", "blocks"])
+            .help("Place the boxes of a type one by one or as blocks"),
+        Arg::with_name("guillotine")
+            .long("guillotine")
+            .help("Only pack boxes which can be cut out edge to edge"),
+        number("clearance", "Gap kept between boxes"),
+        number("margin", "Gap kept between the boxes and the bin walls"),
+        Arg::with_name("time-limit")
+            .long("time-limit")
+            .takes_value(true)
+            .value_name("SECS")
+            .help("Stop evolving after this many seconds"),
+        number(
+            "threads",
+            "Threads decoding a generation, 0 for one per core",
+        ),
+        number("seed", "Seed of the random numbers, for repeatable runs"),
+    ]
+}
+
+/// `params` with the fields given by the flags replaced.
+pub fn apply(matches: &ArgMatches, mut params: Params) -> Result<Params, Box<dyn Error>> {
+    macro_rules! set {
+        ($flag:expr, $field:expr) => {
+            if matches.is_present($flag) {
+                $field = value_t!(matches, $flag, _)?;
+            }
+        };
+    }
+    set!("population-factor", params.population_factor);
+    set!("elites-percentage", params.elites_percentage);
+    set!("mutants-percentage", params.mutants_percentage);
+    set!(
+        "inherit-elite-probability",
+        params.inherit_elite_probability
+    );
+    set!("max-generations", params.max_generations);
+    set!(
+        "max-generations-no-improvement",
+        params.max_generations_no_improvement
+    );
+    set!("exact-threshold", params.exact_threshold);
+    set!("num-bins-weight", params.objective.num_bins);
+    set!("least-load-weight", params.objective.least_load);
+    set!(
+        "center-of-gravity-weight",
+        params.objective.center_of_gravity
+    );
+    set!(
+        "orientation-changes-weight",
+        params.objective.orientation_changes
+    );
+    set!("clearance", params.spacing.clearance);
+    set!("margin", params.spacing.margin);
+    set!("threads", params.threads);
+
+    match matches.value_of("rotation") {
+        Some("3d") => params.box_rotation_type = RotationType::ThreeDimension,
+        Some("2d") => params.box_rotation_type = RotationType::TwoDimension,
+        _ => (),
+    }
+    match matches.value_of("layout") {
+        Some("fill") => params.layout = Layout::Fill,
+        Some("blocks") => params.layout = Layout::Blocks,
+        _ => (),
+    }
+    if matches.is_present("guillotine") {
+        params.guillotine = true;
+    }
+    if matches.is_present("time-limit") {
+        let secs = value_t!(matches, "time-limit", f64)?;
+        params.time_limit = Some(time_limit(secs)?);
+    }
+    if matches.is_present("seed") {
+        params.seed = Some(value_t!(matches, "seed", u64)?);
+    }
+    Ok(params)
+}
+
+/// The fields of `base` replaced by those given in a .toml or .json file.
+pub fn load<P: AsRef<Path>>(path: P, base: Params) -> Result<Params, Box<dyn Error>> {
+    let path = path.as_ref();
+    let text = fs::read_to_string(path)?;
+    let mut value: Value = match path.extension().and_then(|e| e.to_str()) {
+        Some("json") => serde_json::from_str(&text)?,
+        _ => toml::from_str(&text)?,
+    };
+    if let Some(limit) = value.get_mut("time_limit") {
+        if let Some(secs) = limit.as_f64() {
+            *limit = serde_json::to_value(time_limit(secs)?)?;
+        }
+    }
+    let mut params = serde_json::to_value(base)?;
+    merge(&mut params, value);
+    Ok(serde_json::from_value(params)?)
+}
+
+fn time_limit(secs: f64) -> Result<Duration, Box<dyn Error>> {
+    Duration::try_from_secs_f64(secs)
+        .map_err(|_| format!("invalid time limit: {}, expected a number of seconds", secs).into())
+}
+
+// tables are merged field by field, like `objective` and `spacing`.
+fn merge(base: &mut Value, value: Value) {
+    match (base, value) {
+        (Value::Object(base), Value::Object(value)) => {
+            for (key, v) in value {
+                match base.get_mut(&key) {
+                    Some(b) => merge(b, v),
+                    None => {
+                        base.insert(key, v);
+                    }
+                }
+            }
+        }
+        (base, value) => *base = value,
+    }
+}
@@ -0,0 +1,133 @@
+/*
+ * Copyright 2019 Zejun Li
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Statistics of a solution, whichever tool produced it.
+
+use std::collections::HashMap;
+use std::error::Error;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use kaosu_packer::bound::lower_bound;
+use kaosu_packer::geom::Cuboid;
+use kaosu_packer::json::{Problem, Solution};
+
+pub fn command() -> App<'static, 'static> {
+    SubCommand::with_name("stats")
+        .about("Prints the items, load and utilization of every bin of a solution")
+        .arg(
+            Arg::with_name("solution")
+                .required(true)
+                .help("The solution in the JSON format"),
+        )
+        .arg(
+            Arg::with_name("problem")
+                .long("problem")
+                .takes_value(true)
+                .value_name("FILE")
+                .help(
+                    "The problem, for the weights, and for the utilization and the lower bound \
+                     of a version 1 solution",
+                ),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let solution = Solution::load(matches.value_of("solution").unwrap())?;
+    let problem = match matches.value_of("problem") {
+        Some(path) => Some(Problem::load(path)?),
+        None => None,
+    };
+
+    // items weigh the number in their `weight` attribute, if any.
+    let weights: HashMap<&str, f64> = problem
+        .iter()
+        .flat_map(|p| p.items.iter())
+        .filter_map(|item| {
+            let weight = item.attributes.get("weight")?.as_f64()?;
+            Some((item.id.as_str(), weight))
+        })
+        .collect();
+    // solutions since version 2 know their bin, a problem given as well takes precedence.
+    let bin = problem.as_ref().map(|p| p.bin).or(solution.bin);
+    let rotation_type = problem
+        .as_ref()
+        .map(|p| p.params.box_rotation_type)
+        .or(solution.rotation_type);
+    let bin_volume = bin.map(|b| b.volume() as f64);
+
+    println!(
+        "{:>4} {:>6} {:>12} {:>12} {:>10}",
+        "bin", "items", "volume", "utilization", "weight"
+    );
+    let mut total_volume = 0;
+    for (i, bin) in solution.bins.iter().enumerate() {
+        let volume: i64 = bin.placements.iter().map(|p| p.space.volume()).sum();
+        total_volume += volume;
+        let utilization = match bin_volume {
+            Some(bin_volume) => format!("{:.2}%", volume as f64 / bin_volume * 100.),
+            None => "-".to_string(),
+        };
+        let weight = if weights.is_empty() {
+            "-".to_string()
+        } else {
+            let weight: f64 = bin
+                .placements
+                .iter()
+                .filter_map(|p| weights.get(p.id.as_str()))
+                .sum();
+            format!("{}", weight)
+        };
+        println!(
+            "{:>4} {:>6} {:>12} {:>12} {:>10}",
+            i + 1,
+            bin.placements.len(),
+            volume,
+            utilization,
+            weight
+        );
+    }
+
+    let num_bins = solution.bins.len();
+    let placed: usize = solution.bins.iter().map(|b| b.placements.len()).sum();
+    print!("{} items in {} bins", placed, num_bins);
+    if let Some(bin_volume) = bin_volume {
+        print!(
+            ", overall utilization {:.2}%",
+            total_volume as f64 / (bin_volume * num_bins.max(1) as f64) * 100.
+        );
+    }
+    if let (Some(bin), Some(rotation_type)) = (bin, rotation_type) {
+        // the items as placed, in one of their allowed orientations.
+        let items: Vec<Cuboid> = match &problem {
+            Some(problem) => problem.items.iter().map(Cuboid::from).collect(),
+            None => solution
+                .bins
+                .iter()
+                .flat_map(|b| b.placements.iter())
+                .map(|p| Cuboid::new(p.space.width(), p.space.depth(), p.space.height()))
+                .collect(),
+        };
+        let bound = lower_bound(rotation_type, bin, &items);
+        print!(
+            ", lower bound {} (gap {:.2}%)",
+            bound.value(),
+            bound.gap(num_bins) * 100.
+        );
+    }
+    println!();
+    Ok(())
+}
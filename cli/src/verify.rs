@@ -0,0 +1,238 @@
+/*
+ * Copyright 2019 Zejun Li
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Checks a solution against its problem, whichever tool produced it.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use kaosu_packer::geom::{Cuboid, Point, RotationType, Space};
+use kaosu_packer::json::{ItemPlacement, Problem, Solution};
+
+pub fn command() -> App<'static, 'static> {
+    SubCommand::with_name("verify")
+        .about(
+            "Checks that a solution places every item of its problem once, in the bins and apart",
+        )
+        .arg(
+            Arg::with_name("problem")
+                .required(true)
+                .help("The problem in the JSON format"),
+        )
+        .arg(
+            Arg::with_name("solution")
+                .required(true)
+                .help("The solution in the JSON format"),
+        )
+}
+
+pub fn run(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let problem = Problem::load(matches.value_of("problem").unwrap())?;
+    let solution = Solution::load(matches.value_of("solution").unwrap())?;
+
+    let violations = verify(&problem, &solution);
+    for v in &violations {
+        println!("{}", v);
+    }
+    if !violations.is_empty() {
+        return Err(format!("{} violations", violations.len()).into());
+    }
+    let placed: usize = solution.bins.iter().map(|b| b.placements.len()).sum();
+    println!("ok: {} items in {} bins", placed, solution.bins.len());
+    Ok(())
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum Violation {
+    UnknownItem {
+        bin: usize,
+        id: String,
+    },
+    Duplicate {
+        bin: usize,
+        id: String,
+    },
+    Missing {
+        id: String,
+    },
+    /// The placed size is no allowed orientation of the item.
+    WrongSize {
+        bin: usize,
+        id: String,
+    },
+    OutOfBounds {
+        bin: usize,
+        id: String,
+    },
+    Overlap {
+        bin: usize,
+        a: String,
+        b: String,
+    },
+    /// The items don't overlap but are closer than the clearance.
+    TooClose {
+        bin: usize,
+        a: String,
+        b: String,
+    },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Violation::UnknownItem { bin, id } => write!(f, "bin {}: unknown item {}", bin, id),
+            Violation::Duplicate { bin, id } => {
+                write!(f, "bin {}: item {} is placed more than once", bin, id)
+            }
+            Violation::Missing { id } => write!(f, "item {} is not placed", id),
+            Violation::WrongSize { bin, id } => {
+                write!(f, "bin {}: item {} is placed with a wrong size", bin, id)
+            }
+            Violation::OutOfBounds { bin, id } => {
+                write!(f, "bin {}: item {} is out of the bin", bin, id)
+            }
+            Violation::Overlap { bin, a, b } => {
+                write!(f, "bin {}: items {} and {} overlap", bin, a, b)
+            }
+            Violation::TooClose { bin, a, b } => write!(
+                f,
+                "bin {}: items {} and {} are closer than the clearance",
+                bin, a, b
+            ),
+        }
+    }
+}
+
+/// Bins are numbered from 1, like in the summary of `kaosu pack`.
+pub fn verify(problem: &Problem, solution: &Solution) -> Vec<Violation> {
+    let sizes: HashMap<&str, Cuboid> = problem
+        .items
+        .iter()
+        .map(|item| (item.id.as_str(), item.into()))
+        .collect();
+    let spacing = problem.params.spacing;
+    let (m, bin) = (spacing.margin, problem.bin);
+    let bounds = Space::new(
+        Point::new(m, 0, m),
+        Point::new(bin.width - m, bin.height, bin.depth - m),
+    );
+
+    let mut violations = Vec::new();
+    let mut placed: HashMap<&str, usize> = HashMap::new();
+    for (i, solution_bin) in solution.bins.iter().enumerate() {
+        let bin = i + 1;
+        for p in &solution_bin.placements {
+            let id = p.id.clone();
+            let size = match sizes.get(p.id.as_str()) {
+                Some(size) => size,
+                None => {
+                    violations.push(Violation::UnknownItem { bin, id });
+                    continue;
+                }
+            };
+            if placed.insert(&p.id, bin).is_some() {
+                violations.push(Violation::Duplicate {
+                    bin,
+                    id: id.clone(),
+                });
+            }
+            if !fits_as(problem.params.box_rotation_type, size, &p.space) {
+                violations.push(Violation::WrongSize {
+                    bin,
+                    id: id.clone(),
+                });
+            }
+            if !bounds.contains(&p.space) {
+                violations.push(Violation::OutOfBounds { bin, id });
+            }
+        }
+        check_distances(
+            bin,
+            &solution_bin.placements,
+            spacing.clearance,
+            &mut violations,
+        );
+    }
+
+    for item in &problem.items {
+        if !placed.contains_key(item.id.as_str()) {
+            violations.push(Violation::Missing {
+                id: item.id.clone(),
+            });
+        }
+    }
+    violations
+}
+
+fn fits_as(rotation_type: RotationType, size: &Cuboid, space: &Space) -> bool {
+    let (w, d, h) = (space.width(), space.depth(), space.height());
+    match rotation_type {
+        RotationType::TwoDimension => {
+            h == size.height && sorted(&[w, d]) == sorted(&[size.width, size.depth])
+        }
+        RotationType::ThreeDimension => {
+            sorted(&[w, d, h]) == sorted(&[size.width, size.depth, size.height])
+        }
+    }
+}
+
+fn sorted(sides: &[i32]) -> Vec<i32> {
+    let mut sides = sides.to_vec();
+    sides.sort();
+    sides
+}
+
+// items overlap if their intersection has a volume, and are too close if they would overlap
+// once one of them is grown by the clearance on every side. The items are swept by x.
+fn check_distances(
+    bin: usize,
+    placements: &[ItemPlacement],
+    clearance: i32,
+    violations: &mut Vec<Violation>,
+) {
+    let overlap = |a: &Space, b: &Space| a.intersects(b) && a.union(b).volume() > 0;
+    let mut order: Vec<&ItemPlacement> = placements.iter().collect();
+    order.sort_by_key(|p| p.space.bottom_left.x);
+
+    for (i, a) in order.iter().enumerate() {
+        let (lo, hi, c) = (&a.space.bottom_left, &a.space.upper_right, clearance);
+        let grown = Space::new(
+            Point::new(lo.x - c, lo.y - c, lo.z - c),
+            Point::new(hi.x + c, hi.y + c, hi.z + c),
+        );
+        for b in order[i + 1..]
+            .iter()
+            .take_while(|b| b.space.bottom_left.x < grown.upper_right.x)
+        {
+            if overlap(&a.space, &b.space) {
+                violations.push(Violation::Overlap {
+                    bin,
+                    a: a.id.clone(),
+                    b: b.id.clone(),
+                });
+            } else if overlap(&grown, &b.space) {
+                violations.push(Violation::TooClose {
+                    bin,
+                    a: a.id.clone(),
+                    b: b.id.clone(),
+                });
+            }
+        }
+    }
+}
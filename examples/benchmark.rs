@@ -0,0 +1,129 @@
+/*
+ * Copyright 2019 Zejun Li
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Packs standard instances and compares the number of bins to the best known solutions.
+//!
+//! ```text
+//! cargo run --release --example benchmark -- <mpv|thpack|2bp> <best known file> <instance file>...
+//! ```
+//!
+//! The best known file holds an instance name and a number of bins per line, `#` starts a
+//! comment. Pass `-` to go without one.
+
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::Path;
+use std::process;
+
+use kaosu_packer::instances::{self, Instance};
+use kaosu_packer::{pack_boxes_with_report, Params};
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.len() < 3 {
+        eprintln!("usage: benchmark <mpv|thpack|2bp> <best known file|-> <instance file>...");
+        process::exit(2);
+    }
+    if let Err(err) = run(&args[0], &args[1], &args[2..]) {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+}
+
+fn run(format: &str, best_known: &str, files: &[String]) -> Result<(), Box<dyn Error>> {
+    let best_known = if best_known == "-" {
+        HashMap::new()
+    } else {
+        load_best_known(best_known)?
+    };
+
+    println!(
+        "{:<24} {:>6} {:>4} {:>5} {:>5} {:>5} {:>9}",
+        "instance", "boxes", "lb", "bins", "best", "diff", "time (s)"
+    );
+    let (mut total_bins, mut total_best, mut compared) = (0, 0, 0);
+    for file in files {
+        for instance in load_instances(format, file)? {
+            let params = Params {
+                box_rotation_type: instance.rotation_type,
+                ..Params::default()
+            };
+            let report = pack_boxes_with_report(params, instance.bin, &instance.boxes);
+            let bins = report.bins.len();
+            let best = best_known.get(&instance.name).cloned();
+            let (best_col, diff_col) = match best {
+                Some(best) => {
+                    total_bins += bins;
+                    total_best += best;
+                    compared += 1;
+                    (best.to_string(), format!("{:+}", bins as i64 - best as i64))
+                }
+                None => ("-".to_string(), "-".to_string()),
+            };
+            println!(
+                "{:<24} {:>6} {:>4} {:>5} {:>5} {:>5} {:>9.2}",
+                instance.name,
+                instance.boxes.len(),
+                report.lower_bound,
+                bins,
+                best_col,
+                diff_col,
+                report.elapsed.as_secs_f64(),
+            );
+        }
+    }
+
+    if compared > 0 {
+        println!(
+            "{} instances compared, {} bins against {} best known ({:+})",
+            compared,
+            total_bins,
+            total_best,
+            total_bins as i64 - total_best as i64
+        );
+    }
+    Ok(())
+}
+
+fn load_instances(format: &str, file: &str) -> Result<Vec<Instance>, Box<dyn Error>> {
+    let name = Path::new(file)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(file);
+    let reader = BufReader::new(File::open(file)?);
+    let instances = match format {
+        "mpv" => vec![instances::read_mpv(name, reader)?],
+        "thpack" => instances::read_thpack(name, reader)?,
+        "2bp" => instances::read_2bp(name, reader)?,
+        _ => return Err(format!("unknown format: {}", format).into()),
+    };
+    Ok(instances)
+}
+
+fn load_best_known(file: &str) -> Result<HashMap<String, usize>, Box<dyn Error>> {
+    let mut best_known = HashMap::new();
+    for line in fs::read_to_string(file)?.lines() {
+        let line = line.split('#').next().unwrap_or("");
+        let mut fields = line.split_whitespace();
+        if let (Some(name), Some(bins)) = (fields.next(), fields.next()) {
+            best_known.insert(name.to_string(), bins.parse()?);
+        }
+    }
+    Ok(best_known)
+}
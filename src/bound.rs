@@ -0,0 +1,245 @@
+/*
+ * Copyright 2019 Zejun Li
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Lower bounds on the number of bins, following `S. Martello, D. Pisinger and D. Vigo,
+//! “The three-dimensional bin packing problem,” Operations Research, vol. 48, no. 2,
+//! pp. 256–267, 2000.`
+//!
+//! The original bounds assume items with a fixed orientation. Here every item is described
+//! by the smallest extent it can have along each axis over all the orientations allowed by
+//! the rotation type, which keeps the bounds valid when boxes may be rotated.
+
+#[cfg(feature = "serde")]
+use serde::*;
+
+use super::geom::{Cuboid, Point, RotationType, Space};
+use super::placer::rotate_cuboid;
+use super::BoxType;
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LowerBound {
+    pub continuous: usize,
+    pub l1: usize,
+    pub l2: usize,
+}
+
+impl LowerBound {
+    pub fn value(&self) -> usize {
+        self.continuous.max(self.l1).max(self.l2)
+    }
+
+    /// Relative gap between `num_bins` and this bound, `(num_bins - bound) / num_bins`.
+    pub fn gap(&self, num_bins: usize) -> f64 {
+        if num_bins == 0 {
+            return 0.;
+        }
+        let bound = self.value().min(num_bins);
+        (num_bins - bound) as f64 / num_bins as f64
+    }
+}
+
+pub fn lower_bound<'a, T>(
+    rotation_type: RotationType,
+    bin_spec: Cuboid,
+    boxes: &'a [T],
+) -> LowerBound
+where
+    &'a T: Into<Cuboid>,
+{
+    let items = boxes
+        .iter()
+        .map(|b| BoundItem::new(rotation_type, &bin_spec, &b.into(), 1))
+        .collect();
+    bound_of(&bin_spec, items)
+}
+
+pub fn lower_bound_of_types<'a, T>(
+    rotation_type: RotationType,
+    bin_spec: Cuboid,
+    types: &'a [T],
+) -> LowerBound
+where
+    &'a T: Into<BoxType>,
+{
+    let items = types
+        .iter()
+        .map(|t| {
+            let t = t.into();
+            BoundItem::new(rotation_type, &bin_spec, &t.cuboid, t.quantity)
+        })
+        .collect();
+    bound_of(&bin_spec, items)
+}
+
+fn bound_of(bin_spec: &Cuboid, items: Vec<BoundItem>) -> LowerBound {
+    let bin = [
+        i64::from(bin_spec.width),
+        i64::from(bin_spec.height),
+        i64::from(bin_spec.depth),
+    ];
+    let continuous = continuous_bound(&bin, &items);
+    let (mut l1, mut l2) = (0, continuous);
+    for axis in 0..3 {
+        let axis_l1 = l1_along(axis, &bin, &items);
+        l1 = l1.max(axis_l1);
+        l2 = l2.max(l2_along(axis, axis_l1, &bin, &items));
+    }
+
+    LowerBound { continuous, l1, l2 }
+}
+
+// `count` identical items reduced to the data the bounds need. `min_extents` is indexed by
+// axis (x, y, z), matching the bin dimensions (width, height, depth).
+struct BoundItem {
+    volume: i64,
+    count: i64,
+    min_extents: Option<[i64; 3]>,
+}
+
+impl BoundItem {
+    fn new(rotation_type: RotationType, bin_spec: &Cuboid, cuboid: &Cuboid, count: usize) -> Self {
+        let bin_space = Space::from_placement(&Point::new(0, 0, 0), bin_spec);
+        let mut orientations = Vec::with_capacity(6);
+        rotate_cuboid(rotation_type, cuboid, &mut orientations);
+
+        let mut min_extents: Option<[i64; 3]> = None;
+        for o in orientations.iter().filter(|o| o.can_fit_in(&bin_space)) {
+            let extents = [i64::from(o.width), i64::from(o.height), i64::from(o.depth)];
+            min_extents = Some(match min_extents {
+                None => extents,
+                Some(m) => [
+                    m[0].min(extents[0]),
+                    m[1].min(extents[1]),
+                    m[2].min(extents[2]),
+                ],
+            });
+        }
+
+        BoundItem {
+            volume: cuboid.volume(),
+            count: count as i64,
+            min_extents,
+        }
+    }
+}
+
+fn continuous_bound(bin: &[i64; 3], items: &[BoundItem]) -> usize {
+    let bin_volume = bin[0] * bin[1] * bin[2];
+    let total: i64 = items.iter().map(|i| i.volume * i.count).sum();
+    div_ceil(total, bin_volume) as usize
+}
+
+// Items that are wider than half of the bin along both axes other than `axis` can not be
+// placed side by side, so they form a one dimensional bin packing problem along `axis`.
+fn l1_along(axis: usize, bin: &[i64; 3], items: &[BoundItem]) -> usize {
+    let (i, j) = other_axes(axis);
+    let mut sizes = Vec::new();
+    for item in items {
+        if let Some(e) = item.min_extents {
+            if 2 * e[i] > bin[i] && 2 * e[j] > bin[j] {
+                sizes.extend((0..item.count).map(|_| e[axis]));
+            }
+        }
+    }
+    one_dimensional_bound(&sizes, bin[axis])
+}
+
+// Items in `K_v(p, q)` leave no room for items in `K_s(p, q)` beside them, so the slab they
+// occupy along `axis` is lost for the volume of the latter.
+fn l2_along(axis: usize, l1: usize, bin: &[i64; 3], items: &[BoundItem]) -> usize {
+    let (i, j) = other_axes(axis);
+    let bin_volume = bin[0] * bin[1] * bin[2];
+    let face = bin[i] * bin[j];
+    let ps = thresholds(items, i, bin[i]);
+    let qs = thresholds(items, j, bin[j]);
+
+    let mut best = l1;
+    for &p in &ps {
+        for &q in &qs {
+            let mut occupied = 0;
+            for item in items {
+                let e = match item.min_extents {
+                    Some(e) => e,
+                    None => continue,
+                };
+                if e[i] > bin[i] - p && e[j] > bin[j] - q {
+                    occupied += e[axis] * face * item.count;
+                } else if e[i] >= p && e[j] >= q {
+                    occupied += item.volume * item.count;
+                }
+            }
+            best = best.max(div_ceil(occupied, bin_volume) as usize);
+        }
+    }
+    best
+}
+
+// Candidate values of `p` in `[0, capacity / 2]` at which `K_v` or `K_s` may change.
+fn thresholds(items: &[BoundItem], axis: usize, capacity: i64) -> Vec<i64> {
+    let mut values = vec![0];
+    for e in items.iter().filter_map(|item| item.min_extents) {
+        let (small, rest) = (e[axis], capacity - e[axis] + 1);
+        if 2 * small <= capacity {
+            values.push(small);
+        }
+        if rest > 0 && 2 * rest <= capacity {
+            values.push(rest);
+        }
+    }
+    values.sort_unstable();
+    values.dedup();
+    values
+}
+
+// The `L2` bound of Martello and Toth for the one dimensional bin packing problem.
+fn one_dimensional_bound(sizes: &[i64], capacity: i64) -> usize {
+    let mut alphas: Vec<i64> = sizes
+        .iter()
+        .cloned()
+        .filter(|&s| 2 * s <= capacity)
+        .collect();
+    alphas.push(0);
+    alphas.sort_unstable();
+    alphas.dedup();
+
+    let mut best = 0;
+    for alpha in alphas {
+        let (mut n1, mut n2, mut sum2, mut sum3) = (0, 0, 0, 0);
+        for &s in sizes {
+            if s > capacity - alpha {
+                n1 += 1;
+            } else if 2 * s > capacity {
+                n2 += 1;
+                sum2 += s;
+            } else if s >= alpha {
+                sum3 += s;
+            }
+        }
+        let residual = n2 as i64 * capacity - sum2;
+        let extra = div_ceil((sum3 - residual).max(0), capacity) as usize;
+        best = best.max(n1 + n2 + extra);
+    }
+    best
+}
+
+fn other_axes(axis: usize) -> (usize, usize) {
+    ((axis + 1) % 3, (axis + 2) % 3)
+}
+
+fn div_ceil(a: i64, b: i64) -> i64 {
+    (a + b - 1) / b
+}
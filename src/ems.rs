@@ -0,0 +1,167 @@
+/*
+ * Copyright 2019 Zejun Li
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::cmp::Reverse;
+
+use super::geom::{Cuboid, Space};
+
+// A new empty space cut from an intersected one, tagged with the side of the allocated space
+// it lies on (`0..6`, in the order of `difference_process`), or `UNCHANGED` for a space only
+// touching the allocated one, which stays in the index as it is.
+pub type SideSpace = (usize, Space);
+
+pub const UNCHANGED: usize = 6;
+
+// The empty maximal spaces of a bin, kept sorted by volume from the largest, so the spaces
+// large enough for a box are a prefix of the list found by binary search. The largest extent
+// along each axis over all the spaces is tracked too, which rejects a bin that can not hold a
+// box without a scan.
+#[derive(Clone, Debug)]
+pub struct EmsIndex {
+    entries: Vec<(i64, Space)>,
+    fresh: Vec<(i64, Space)>,
+    max_extents: Cuboid,
+}
+
+impl EmsIndex {
+    pub fn new(space: Space) -> Self {
+        let mut index = EmsIndex {
+            entries: Vec::new(),
+            fresh: Vec::new(),
+            max_extents: Cuboid::new(0, 0, 0),
+        };
+        index.reset(space);
+        index
+    }
+
+    pub fn reset(&mut self, space: Space) {
+        self.entries.clear();
+        self.entries.push((space.volume(), space));
+        self.max_extents = Cuboid::new(0, 0, 0);
+        grow(&mut self.max_extents, &space);
+    }
+
+    pub fn nth(&self, idx: usize) -> &Space {
+        &self.entries[idx].1
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Space> {
+        self.entries.iter().map(|(_, s)| s)
+    }
+
+    // whether any of the orientations could fit in some space, judging by extents only.
+    pub fn may_hold(&self, orientations: &[Cuboid]) -> bool {
+        let max = &self.max_extents;
+        orientations
+            .iter()
+            .any(|o| o.width <= max.width && o.height <= max.height && o.depth <= max.depth)
+    }
+
+    // the spaces whose volume is not less than `volume`.
+    pub fn at_least(&self, volume: i64) -> impl Iterator<Item = &Space> {
+        let n = self.entries.partition_point(|&(v, _)| v >= volume);
+        self.entries[..n].iter().map(|(_, s)| s)
+    }
+
+    // the indices of the spaces intersecting `space`, touching it included, in ascending order.
+    pub fn intersecting(&self, space: &Space, result: &mut Vec<usize>) {
+        let found = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, ems))| ems.intersects(space))
+            .map(|(i, _)| i);
+        result.extend(found);
+    }
+
+    // remove the spaces at `removed` (in ascending order) and the ones rejected by `filter`,
+    // then insert the spaces in `added` which are not contained in another added space.
+    // Spaces tagged `UNCHANGED` are still in the index and only take part in the containment
+    // check.
+    pub fn update<F>(&mut self, removed: &[usize], added: &mut [SideSpace], mut filter: F)
+    where
+        F: FnMut(&Space) -> bool,
+    {
+        let max = self.max_extents;
+        let mut shrunk = false;
+        let mut removed = removed.iter().peekable();
+        let mut i = 0;
+        self.entries.retain(|(_, s)| {
+            let hit = removed.peek() == Some(&&i);
+            if hit {
+                removed.next();
+            }
+            i += 1;
+            let keep = !hit && filter(s);
+            if !keep {
+                shrunk |=
+                    s.width() == max.width || s.height() == max.height || s.depth() == max.depth;
+            }
+            keep
+        });
+
+        // Spaces on different sides of the allocated space can not contain each other, as each
+        // one is bounded by its own face of the allocated space while spanning the others, so
+        // a space only needs to be checked against the larger ones on its side and the
+        // unchanged ones.
+        added.sort_unstable_by_key(|&(side, s)| (side, Reverse(s.volume())));
+        let unchanged = added.partition_point(|&(side, _)| side < UNCHANGED);
+        self.fresh.clear();
+        for (i, &(side, this)) in added[..unchanged].iter().enumerate() {
+            let start = added[..i].partition_point(|&(s, _)| s < side);
+            let dominated = (start..i).chain(unchanged..added.len()).any(|j| {
+                let other = &added[j].1;
+                other.contains(&this) && (j < i || *other != this)
+            });
+            if !dominated {
+                self.fresh.push((this.volume(), this));
+            }
+        }
+        self.fresh.sort_unstable_by_key(|&(v, _)| Reverse(v));
+
+        // merge the fresh spaces in from the back, so every entry moves once.
+        let (mut i, mut j) = (self.entries.len(), self.fresh.len());
+        self.entries.extend_from_slice(&self.fresh);
+        while j > 0 {
+            let (old, new) = (&self.entries[i.max(1) - 1], &self.fresh[j - 1]);
+            let k = i + j - 1;
+            if i > 0 && old.0 < new.0 {
+                self.entries[k] = *old;
+                i -= 1;
+            } else {
+                self.entries[k] = *new;
+                j -= 1;
+            }
+        }
+
+        if shrunk {
+            self.max_extents = Cuboid::new(0, 0, 0);
+            for (_, s) in &self.entries {
+                grow(&mut self.max_extents, s);
+            }
+        } else {
+            for (_, s) in &self.fresh {
+                grow(&mut self.max_extents, s);
+            }
+        }
+    }
+}
+
+fn grow(max: &mut Cuboid, space: &Space) {
+    max.width = max.width.max(space.width());
+    max.height = max.height.max(space.height());
+    max.depth = max.depth.max(space.depth());
+}
@@ -0,0 +1,205 @@
+/*
+ * Copyright 2019 Zejun Li
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::time::Duration;
+
+use super::clock::Instant;
+use super::geom::*;
+use super::placer::{rotate_cuboid, InnerBin, InnerBox, InnerPlacement, InnerSolution};
+
+// stop searching after visiting this many nodes, the incumbent is returned as is.
+const NODE_LIMIT: usize = 1_000_000;
+
+// look at the clock once every this many nodes.
+const CLOCK_INTERVAL: usize = 1024;
+
+// Depth first branch and bound over the boxes sorted by volume in descending order. Each box
+// is either put at the origin of an empty maximal space of an opened bin, or into a new bin.
+// The search is not complete, some packings such as a pinwheel can not be reached in that
+// order, so only a solution meeting the lower bound is known to be optimal.
+pub struct ExactSolver {
+    boxes: Vec<InnerBox>,
+    bin_spec: Cuboid,
+    rotation_type: RotationType,
+    guillotine: bool,
+    lower_bound: usize,
+    time_limit: Option<Duration>,
+    start: Instant,
+    nodes: usize,
+    stopped: bool,
+
+    order: Vec<usize>,
+    remaining_volume: Vec<i64>,
+    bins: Vec<InnerBin>,
+    placements: Vec<InnerPlacement>,
+    best: Option<Vec<InnerPlacement>>,
+    best_bins: usize,
+}
+
+impl ExactSolver {
+    // every box is on its own, with a quantity of one. The search stops like at the node
+    // limit once `time_limit` has passed since `solve` was called.
+    pub fn new(
+        boxes: Vec<InnerBox>,
+        bin_spec: Cuboid,
+        rotation_type: RotationType,
+        guillotine: bool,
+        lower_bound: usize,
+        time_limit: Option<Duration>,
+    ) -> Self {
+        let mut order: Vec<usize> = (0..boxes.len()).collect();
+        order.sort_by(|&a, &b| boxes[b].volume.cmp(&boxes[a].volume));
+
+        let mut remaining_volume = vec![0; order.len() + 1];
+        for i in (0..order.len()).rev() {
+            remaining_volume[i] = remaining_volume[i + 1] + boxes[order[i]].volume;
+        }
+
+        ExactSolver {
+            best_bins: boxes.len() + 1,
+            boxes,
+            bin_spec,
+            rotation_type,
+            guillotine,
+            lower_bound,
+            time_limit,
+            start: Instant::now(),
+            nodes: 0,
+            stopped: false,
+            order,
+            remaining_volume,
+            bins: Vec::new(),
+            placements: Vec::new(),
+            best: None,
+        }
+    }
+
+    // the best solution found, and whether it meets the lower bound, proving its number of
+    // bins optimal.
+    pub fn solve(mut self) -> Option<(InnerSolution, bool)> {
+        self.start = Instant::now();
+        self.search(0);
+
+        let proven = self.best_bins <= self.lower_bound;
+        let placements = self.best?;
+        let solution = InnerSolution::new(self.best_bins, placements, &self.boxes);
+        Some((solution, proven))
+    }
+
+    fn search(&mut self, depth: usize) {
+        if self.stopped || self.best_bins <= self.lower_bound {
+            return;
+        }
+        self.nodes += 1;
+        let out_of_time = self.nodes.is_multiple_of(CLOCK_INTERVAL)
+            && self
+                .time_limit
+                .is_some_and(|limit| self.start.elapsed() >= limit);
+        if self.nodes >= NODE_LIMIT || out_of_time {
+            self.stopped = true;
+            return;
+        }
+
+        if depth == self.order.len() {
+            self.best_bins = self.bins.len();
+            self.best = Some(self.placements.clone());
+            return;
+        }
+        if self.bins_needed(depth) >= self.best_bins {
+            return;
+        }
+
+        let box_idx = self.order[depth];
+        for bin_no in 0..self.bins.len() {
+            self.branch(depth, bin_no, box_idx);
+        }
+
+        if self.bins.len() + 1 < self.best_bins {
+            self.bins
+                .push(InnerBin::new(self.bin_spec, self.guillotine));
+            self.branch(depth, self.bins.len() - 1, box_idx);
+            self.bins.pop();
+        }
+    }
+
+    fn branch(&mut self, depth: usize, bin_no: usize, box_idx: usize) {
+        let (min_dimension, min_volume) = self.min_dimension_and_volume(depth + 1);
+        for space in self.candidates(bin_no, box_idx) {
+            let saved = self.bins[bin_no].clone();
+            self.bins[bin_no].allocate_space(&space, |ns| {
+                let (w, d, h) = (ns.width(), ns.depth(), ns.height());
+                w.min(d).min(h) >= min_dimension && ns.volume() >= min_volume
+            });
+            self.placements
+                .push(InnerPlacement::new(space, bin_no, box_idx));
+
+            self.search(depth + 1);
+
+            self.placements.pop();
+            self.bins[bin_no] = saved;
+        }
+    }
+
+    // all distinct spaces the box can take in the bin, the ones farthest from the upper right
+    // corner of the bin first.
+    fn candidates(&self, bin_no: usize, box_idx: usize) -> Vec<Space> {
+        let mut orientations = Vec::with_capacity(6);
+        rotate_cuboid(
+            self.rotation_type,
+            &self.boxes[box_idx].cuboid,
+            &mut orientations,
+        );
+
+        let mut spaces = Vec::new();
+        for ems in self.bins[bin_no].empty_spaces.iter() {
+            for o in orientations.iter().filter(|o| o.can_fit_in(ems)) {
+                let space = Space::from_placement(ems.origin(), o);
+                if !spaces.contains(&space) {
+                    spaces.push(space);
+                }
+            }
+        }
+
+        let corner = Point::new(
+            self.bin_spec.width,
+            self.bin_spec.height,
+            self.bin_spec.depth,
+        );
+        spaces.sort_by_key(|s| -corner.distance2_from(&s.upper_right));
+        spaces
+    }
+
+    fn bins_needed(&self, depth: usize) -> usize {
+        let bin_volume = self.bin_spec.volume();
+        let free: i64 = self
+            .bins
+            .iter()
+            .map(|bin| bin_volume - bin.used_volume)
+            .sum();
+        let overflow = (self.remaining_volume[depth] - free).max(0);
+        self.bins.len() + ((overflow + bin_volume - 1) / bin_volume) as usize
+    }
+
+    fn min_dimension_and_volume(&self, depth: usize) -> (i32, i64) {
+        let (mut min_d, mut min_v) = (i32::MAX, i64::MAX);
+        for &box_idx in &self.order[depth..] {
+            let b = &self.boxes[box_idx];
+            min_d = min_d.min(b.smallest_dimension);
+            min_v = min_v.min(b.volume);
+        }
+        (min_d, min_v)
+    }
+}
@@ -15,18 +15,53 @@
  */
 
 use std::mem;
+use std::time::Duration;
 
 use rand::prelude::*;
+use rand::rngs::SmallRng;
 #[cfg(feature = "rayon")]
 use rayon::prelude::*;
 
+use super::clock::Instant;
+
 pub type Chromosome = Vec<f32>;
 
-#[derive(Clone)]
-struct InnerChromosome<S: Clone> {
-    chromosome: Chromosome,
-    solution: S,
-    fitness: f64,
+// A generation stored in flat buffers which are reused from one generation to the next. The
+// genes of individual `i` are `genes[i * len..(i + 1) * len]`, individuals never move inside
+// the buffers and `ranking` lists them by fitness instead.
+#[derive(Default)]
+struct Population<S> {
+    len: usize,
+    genes: Vec<f32>,
+    solutions: Vec<S>,
+    fitness: Vec<f64>,
+    ranking: Vec<usize>,
+}
+
+impl<S: Default> Population<S> {
+    fn new(size: usize, len: usize) -> Self {
+        Population {
+            len,
+            genes: vec![0.; size * len],
+            solutions: (0..size).map(|_| S::default()).collect(),
+            fitness: vec![0.; size],
+            ranking: (0..size).collect(),
+        }
+    }
+
+    fn genes(&self, i: usize) -> &[f32] {
+        &self.genes[i * self.len..(i + 1) * self.len]
+    }
+
+    fn best(&self) -> usize {
+        self.ranking[0]
+    }
+
+    fn rank(&mut self) {
+        let fitness = &self.fitness;
+        self.ranking
+            .sort_unstable_by(|&a, &b| fitness[a].partial_cmp(&fitness[b]).unwrap());
+    }
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -37,28 +72,46 @@ pub struct Params {
     pub inherit_elite_probability: f64,
     pub max_generations: i32,
     pub max_generations_no_improvement: i32,
+    // stop evolving once this much time has passed.
+    pub time_limit: Option<Duration>,
+    // decode the individuals of a generation in parallel, when built with rayon.
+    pub parallel: bool,
+    // every individual draws its random numbers from its own generator, seeded from this, its
+    // generation and its index, so the evolution does not depend on the threads.
+    pub seed: u64,
 }
 
 pub trait Decoder {
     #[cfg(not(feature = "rayon"))]
-    type Solution: Clone;
+    type Solution: Clone + Default;
 
     #[cfg(feature = "rayon")]
-    type Solution: Clone + Sync + Send;
+    type Solution: Clone + Default + Sync + Send;
 
-    fn decode_chromosome(&mut self, individual: &Chromosome) -> Self::Solution;
+    // decode the genes into `solution`, reusing the memory it holds. Only what `fitness_of`
+    // and `is_optimal` look at has to be filled in, the evolution never reads the rest.
+    fn decode_chromosome(&mut self, genes: &[f32], solution: &mut Self::Solution);
+    // decode the genes into a complete solution, called for the final best individual only.
+    fn materialize(&mut self, genes: &[f32]) -> Self::Solution;
     fn fitness_of(&self, solution: &Self::Solution) -> f64;
     fn reset(&mut self);
+
+    // whether the solution is known to be optimal, so the evolution can stop early.
+    fn is_optimal(&self, _solution: &Self::Solution) -> bool {
+        false
+    }
 }
 
 #[cfg(feature = "rayon")]
 pub trait Generator: Sync + Send {
-    fn generate_individual(&self) -> Chromosome;
+    fn chromosome_len(&self) -> usize;
+    fn generate_individual<R: Rng>(&self, rng: &mut R, genes: &mut [f32]);
 }
 
 #[cfg(not(feature = "rayon"))]
 pub trait Generator {
-    fn generate_individual(&self) -> Chromosome;
+    fn chromosome_len(&self) -> usize;
+    fn generate_individual<R: Rng>(&self, rng: &mut R, genes: &mut [f32]);
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -73,9 +126,14 @@ impl RandGenerator {
 }
 
 impl Generator for RandGenerator {
-    fn generate_individual(&self) -> Vec<f32> {
-        let mut rng = thread_rng();
-        (0..self.length).map(|_| rng.gen()).collect()
+    fn chromosome_len(&self) -> usize {
+        self.length
+    }
+
+    fn generate_individual<R: Rng>(&self, rng: &mut R, genes: &mut [f32]) {
+        for gene in genes {
+            *gene = rng.gen();
+        }
     }
 }
 
@@ -88,36 +146,60 @@ where
     generator: G,
     decoder_factory: F,
     params: Params,
-
-    // reuse population vec between generations.
-    population: Vec<InnerChromosome<D::Solution>>,
-    population1: Vec<InnerChromosome<D::Solution>>,
+    seeds: Vec<Chromosome>,
+    generations: usize,
+    num_decoded: usize,
+    started: Instant,
+    // the generation being bred.
+    generation: usize,
+
+    // reuse population buffers between generations.
+    population: Population<D::Solution>,
+    population1: Population<D::Solution>,
 }
 
 macro_rules! define_solve_and_new {
     () => {
         pub fn new(params: Params, generator: G, decoder_factory: F) -> Solver<G, D, F> {
+            let len = generator.chromosome_len();
             Solver {
                 generator,
                 decoder_factory,
                 params,
-                population: Vec::with_capacity(params.population_size),
-                population1: Vec::with_capacity(params.population_size),
+                seeds: Vec::new(),
+                generations: 0,
+                num_decoded: 0,
+                started: Instant::now(),
+                generation: 0,
+                population: Population::new(params.population_size, len),
+                population1: Population::new(params.population_size, len),
             }
         }
 
+        // add an individual to the first generation in place of a random one.
+        pub fn seed(&mut self, chromosome: Chromosome) {
+            self.seeds.push(chromosome);
+        }
+
         pub fn solve(&mut self) -> D::Solution {
+            self.started = Instant::now();
             let mut generation = 0;
             let mut generations_no_improvement = 0;
 
+            self.generation = 0;
             self.init_first_generation();
+            self.num_decoded = self.params.population_size;
+            let mut decoder = (self.decoder_factory)();
 
             while generation < self.params.max_generations
                 && generations_no_improvement < self.params.max_generations_no_improvement
+                && !self.out_of_time()
+                && !decoder.is_optimal(&self.population.solutions[self.population.best()])
             {
-                let prev_fitness = self.population[0].fitness;
+                let prev_fitness = self.population.fitness[self.population.best()];
+                self.generation = generation as usize + 1;
                 self.evolve_new_generation();
-                let curr_fitness = self.population[0].fitness;
+                let curr_fitness = self.population.fitness[self.population.best()];
 
                 if curr_fitness < prev_fitness {
                     generations_no_improvement = 0;
@@ -126,9 +208,11 @@ macro_rules! define_solve_and_new {
                 }
 
                 generation += 1;
+                self.num_decoded += self.params.population_size - self.params.num_elites;
             }
 
-            self.population[0].solution.clone()
+            self.generations = generation as usize;
+            decoder.materialize(self.population.genes(self.population.best()))
         }
     };
 }
@@ -139,51 +223,153 @@ where
     D: Decoder,
     F: Fn() -> D,
 {
+    // the number of generations evolved by the last call to `solve`.
+    pub fn generations(&self) -> usize {
+        self.generations
+    }
+
+    // the number of chromosomes decoded by the last call to `solve`.
+    pub fn num_decoded(&self) -> usize {
+        self.num_decoded
+    }
+
+    fn rng_for(&self, i: usize) -> SmallRng {
+        let stream = ((self.generation as u64) << 32) ^ i as u64;
+        SmallRng::seed_from_u64(self.params.seed ^ stream)
+    }
+
+    fn out_of_time(&self) -> bool {
+        self.params
+            .time_limit
+            .is_some_and(|limit| self.started.elapsed() >= limit)
+    }
+
     #[inline]
     fn crossover(
         &self,
-        elite: &Chromosome,
-        non_elite: &Chromosome,
-        rng: &mut ThreadRng,
-    ) -> Chromosome {
-        let mut offspring = Vec::with_capacity(elite.len());
-        offspring.extend((0..elite.len()).map(|i| {
+        elite: &[f32],
+        non_elite: &[f32],
+        offspring: &mut [f32],
+        rng: &mut SmallRng,
+    ) {
+        for (i, gene) in offspring.iter_mut().enumerate() {
             let p: f64 = rng.gen();
-            if p <= self.params.inherit_elite_probability {
+            *gene = if p <= self.params.inherit_elite_probability {
                 elite[i]
             } else {
                 non_elite[i]
-            }
-        }));
-        offspring
+            };
+        }
     }
 
     #[inline]
-    fn pickup_parents_for_crossover(&self, rng: &mut ThreadRng) -> (&Chromosome, &Chromosome) {
+    fn pickup_parents_for_crossover(&self, rng: &mut SmallRng) -> (&[f32], &[f32]) {
         let elite_size = self.params.num_elites;
         let non_elite_size = self.params.population_size - elite_size;
-        let elite = &self.population[rng.gen_range(0, elite_size)];
-        let non_elite = &self.population[elite_size + rng.gen_range(0, non_elite_size)];
+        let population = &self.population;
+        let elite = population.ranking[rng.gen_range(0, elite_size)];
+        let non_elite = population.ranking[elite_size + rng.gen_range(0, non_elite_size)];
 
-        (&elite.chromosome, &non_elite.chromosome)
+        (population.genes(elite), population.genes(non_elite))
     }
 
     #[inline]
-    fn sort_population(population: &mut Vec<InnerChromosome<D::Solution>>) {
-        population.sort_unstable_by(|a, b| a.fitness.partial_cmp(&b.fitness).unwrap());
+    fn initial_individual(
+        &self,
+        decoder: &mut D,
+        i: usize,
+        genes: &mut [f32],
+        solution: &mut D::Solution,
+    ) -> f64 {
+        match self.seeds.get(i) {
+            Some(seed) => genes.copy_from_slice(seed),
+            None => self
+                .generator
+                .generate_individual(&mut self.rng_for(i), genes),
+        }
+        // individuals left undecoded when the time is up rank last.
+        if i >= self.seeds.len() && self.out_of_time() {
+            return f64::INFINITY;
+        }
+        Self::decode_chromosome(decoder, genes, solution)
     }
 
+    // fill in the `i`-th individual of the next generation: the elites are copied from the
+    // current one, followed by the mutants and then the offsprings.
     #[inline]
-    fn decode_chromosome(decoder: &mut D, chromosome: Chromosome) -> InnerChromosome<D::Solution> {
-        let solution = decoder.decode_chromosome(&chromosome);
-        let fitness = decoder.fitness_of(&solution);
+    fn breed(
+        &self,
+        decoder: &mut D,
+        i: usize,
+        genes: &mut [f32],
+        solution: &mut D::Solution,
+    ) -> f64 {
+        let num_elites = self.params.num_elites;
+        if i < num_elites {
+            let elite = self.population.ranking[i];
+            genes.copy_from_slice(self.population.genes(elite));
+            solution.clone_from(&self.population.solutions[elite]);
+            return self.population.fitness[elite];
+        }
+
+        let mut rng = self.rng_for(i);
+        if i < num_elites + self.params.num_mutants {
+            self.generator.generate_individual(&mut rng, genes);
+        } else {
+            let (elite, non_elite) = self.pickup_parents_for_crossover(&mut rng);
+            self.crossover(elite, non_elite, genes, &mut rng);
+        }
+        if self.out_of_time() {
+            return f64::INFINITY;
+        }
+        Self::decode_chromosome(decoder, genes, solution)
+    }
+
+    #[inline]
+    fn decode_chromosome(decoder: &mut D, genes: &[f32], solution: &mut D::Solution) -> f64 {
+        decoder.decode_chromosome(genes, solution);
+        let fitness = decoder.fitness_of(solution);
         decoder.reset();
+        fitness
+    }
+
+    fn init_first_generation_sequential(&mut self) {
+        let mut decoder = (self.decoder_factory)();
+        let mut population = mem::take(&mut self.population);
+        let len = population.len;
+
+        let individuals = population
+            .genes
+            .chunks_mut(len)
+            .zip(population.solutions.iter_mut())
+            .zip(population.fitness.iter_mut())
+            .enumerate();
+        for (i, ((genes, solution), fitness)) in individuals {
+            *fitness = self.initial_individual(&mut decoder, i, genes, solution);
+        }
+
+        population.rank();
+        self.population = population;
+    }
 
-        InnerChromosome {
-            chromosome,
-            solution,
-            fitness,
+    fn evolve_new_generation_sequential(&mut self) {
+        let mut decoder = (self.decoder_factory)();
+        let mut next = mem::take(&mut self.population1);
+        let len = next.len;
+
+        let individuals = next
+            .genes
+            .chunks_mut(len)
+            .zip(next.solutions.iter_mut())
+            .zip(next.fitness.iter_mut())
+            .enumerate();
+        for (i, ((genes, solution), fitness)) in individuals {
+            *fitness = self.breed(&mut decoder, i, genes, solution);
         }
+
+        // rank the new generation and swap backend buffers.
+        next.rank();
+        self.population1 = mem::replace(&mut self.population, next);
     }
 }
 
@@ -197,55 +383,59 @@ where
     define_solve_and_new!();
 
     fn evolve_new_generation(&mut self) {
-        let num_elites = self.params.num_elites;
-        let num_mutants = self.params.num_mutants;
-        let num_offsprings = self.params.population_size - num_elites - num_mutants;
+        if !self.params.parallel {
+            return self.evolve_new_generation_sequential();
+        }
 
+        // move the next generation out so the current one can be shared by the workers.
+        let mut next = mem::take(&mut self.population1);
+        let len = next.len;
         let decoder_factory = &self.decoder_factory;
-        let generator = &self.generator;
-        let mut dummy = Vec::new();
+        let this = &*self;
 
         // reuse decoder in mutant and crossover.
-        mem::swap(&mut dummy, &mut self.population1);
-        (0..(num_mutants + num_offsprings))
-            .into_par_iter()
-            .map_init(
-                || (decoder_factory(), thread_rng()),
-                |&mut (ref mut decoder, ref mut rng), i| {
-                    if i < num_mutants {
-                        Self::decode_chromosome(decoder, generator.generate_individual())
-                    } else {
-                        let (elite, non_elite) = self.pickup_parents_for_crossover(rng);
-                        let offspring = self.crossover(elite, non_elite, rng);
-                        Self::decode_chromosome(decoder, offspring)
-                    }
+        next.genes
+            .par_chunks_mut(len)
+            .zip(next.solutions.par_iter_mut())
+            .zip(next.fitness.par_iter_mut())
+            .enumerate()
+            .for_each_init(
+                decoder_factory,
+                |decoder, (i, ((genes, solution), fitness))| {
+                    *fitness = this.breed(decoder, i, genes, solution);
                 },
-            )
-            .collect_into_vec(&mut dummy);
-        mem::swap(&mut dummy, &mut self.population1);
+            );
 
-        // copy elites (must after collect_into_vec)
-        for elite in &self.population[0..num_elites] {
-            self.population1.push(elite.clone());
-        }
-
-        // sort the new generation and swap backend vec.
-        Self::sort_population(&mut self.population1);
-        // TODO: we can reuse the memory of individual's vector inside population vector.
-        self.population.clear();
-        mem::swap(&mut self.population, &mut self.population1);
+        // rank the new generation and swap backend buffers.
+        next.rank();
+        self.population1 = mem::replace(&mut self.population, next);
     }
 
     fn init_first_generation(&mut self) {
+        if !self.params.parallel {
+            return self.init_first_generation_sequential();
+        }
+
+        let mut population = mem::take(&mut self.population);
+        let len = population.len;
         let decoder_factory = &self.decoder_factory;
-        let generator = &self.generator;
-        (0..self.params.population_size)
-            .into_par_iter()
-            .map_init(decoder_factory, |decoder, _| {
-                Self::decode_chromosome(decoder, generator.generate_individual())
-            })
-            .collect_into_vec(&mut self.population);
-        Self::sort_population(&mut self.population);
+        let this = &*self;
+
+        population
+            .genes
+            .par_chunks_mut(len)
+            .zip(population.solutions.par_iter_mut())
+            .zip(population.fitness.par_iter_mut())
+            .enumerate()
+            .for_each_init(
+                decoder_factory,
+                |decoder, (i, ((genes, solution), fitness))| {
+                    *fitness = this.initial_individual(decoder, i, genes, solution);
+                },
+            );
+
+        population.rank();
+        self.population = population;
     }
 }
 
@@ -259,46 +449,10 @@ where
     define_solve_and_new!();
 
     fn init_first_generation(&mut self) {
-        let mut decoder = (self.decoder_factory)();
-        let generator = &self.generator;
-        self.population.extend(
-            (0..self.params.population_size)
-                .map(|_| Self::decode_chromosome(&mut decoder, generator.generate_individual())),
-        );
-        Self::sort_population(&mut self.population);
+        self.init_first_generation_sequential();
     }
 
     fn evolve_new_generation(&mut self) {
-        let mut decoder = (self.decoder_factory)();
-        let mut rng = thread_rng();
-        let num_elites = self.params.num_elites;
-        let num_mutants = self.params.num_mutants;
-        let num_offsprings = self.params.population_size - num_elites - num_mutants;
-
-        // copy elites to next generation.
-        for elite in &self.population[0..num_elites] {
-            self.population1.push(elite.clone());
-        }
-
-        // generate mutants from generator.
-        for _ in 0..num_mutants {
-            let mutant = self.generator.generate_individual();
-            let mutant = Self::decode_chromosome(&mut decoder, mutant);
-            self.population1.push(mutant);
-        }
-
-        // crossover offsprings.
-        for _ in 0..num_offsprings {
-            let (elite, non_elite) = self.pickup_parents_for_crossover(&mut rng);
-            let offspring = self.crossover(elite, non_elite, &mut rng);
-            self.population1
-                .push(Self::decode_chromosome(&mut decoder, offspring));
-        }
-
-        // sort the new generation and swap backend vec.
-        Self::sort_population(&mut self.population1);
-        // TODO: we can reuse the memory of individual's vector inside population vector.
-        self.population.clear();
-        mem::swap(&mut self.population, &mut self.population1);
+        self.evolve_new_generation_sequential();
     }
 }
@@ -30,11 +30,15 @@ impl Point {
         Point { x, y, z }
     }
 
-    pub fn distance2_from(&self, other: &Self) -> i32 {
-        let dx = self.x - other.x;
-        let dy = self.y - other.y;
-        let dz = self.z - other.z;
-        dx * dx + dy * dy + dz * dz
+    /// The squared euclidean distance, panics if it overflows `i64`.
+    pub fn distance2_from(&self, other: &Self) -> i64 {
+        let dx = i64::from(self.x) - i64::from(other.x);
+        let dy = i64::from(self.y) - i64::from(other.y);
+        let dz = i64::from(self.z) - i64::from(other.z);
+        dx.checked_mul(dx)
+            .and_then(|d| dy.checked_mul(dy).and_then(|dy2| d.checked_add(dy2)))
+            .and_then(|d| dz.checked_mul(dz).and_then(|dz2| d.checked_add(dz2)))
+            .expect("squared distance overflows i64")
     }
 
     fn scalar_less_than(&self, other: &Self) -> bool {
@@ -66,8 +70,9 @@ impl Cuboid {
         }
     }
 
-    pub fn volume(&self) -> i32 {
-        self.width * self.depth * self.height
+    /// Panics if the volume overflows `i64`.
+    pub fn volume(&self) -> i64 {
+        volume_of(self.width, self.depth, self.height)
     }
 
     pub fn can_fit_in(&self, space: &Space) -> bool {
@@ -75,9 +80,41 @@ impl Cuboid {
     }
 }
 
-impl Into<Cuboid> for &Cuboid {
-    fn into(self) -> Cuboid {
-        *self
+impl From<&Cuboid> for Cuboid {
+    fn from(cuboid: &Cuboid) -> Self {
+        *cuboid
+    }
+}
+
+/// A rectangle in the plane of the width and the height.
+#[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Rect {
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Rect {
+    pub fn new(width: i32, height: i32) -> Self {
+        Rect { width, height }
+    }
+
+    /// Panics if the area overflows `i64`.
+    pub fn area(&self) -> i64 {
+        volume_of(self.width, 1, self.height)
+    }
+}
+
+impl From<&Rect> for Rect {
+    fn from(rect: &Rect) -> Self {
+        *rect
+    }
+}
+
+// rectangles are packed as cuboids of depth 1.
+impl From<&Rect> for Cuboid {
+    fn from(rect: &Rect) -> Self {
+        Cuboid::new(rect.width, 1, rect.height)
     }
 }
 
@@ -97,9 +134,10 @@ impl Space {
     }
 
     pub fn from_placement(origin: &Point, rect: &Cuboid) -> Self {
-        let x = origin.x + rect.width;
-        let y = origin.y + rect.height;
-        let z = origin.z + rect.depth;
+        let overflow = "coordinate overflows i32";
+        let x = origin.x.checked_add(rect.width).expect(overflow);
+        let y = origin.y.checked_add(rect.height).expect(overflow);
+        let z = origin.z.checked_add(rect.depth).expect(overflow);
 
         Space {
             bottom_left: *origin,
@@ -151,7 +189,15 @@ impl Space {
         Space::new(Point::new(bx, by, bz), Point::new(ux, uy, uz))
     }
 
-    pub fn volume(&self) -> i32 {
-        self.width() * self.height() * self.depth()
+    /// Panics if the volume overflows `i64`.
+    pub fn volume(&self) -> i64 {
+        volume_of(self.width(), self.depth(), self.height())
     }
 }
+
+fn volume_of(width: i32, depth: i32, height: i32) -> i64 {
+    i64::from(width)
+        .checked_mul(i64::from(depth))
+        .and_then(|area| area.checked_mul(i64::from(height)))
+        .expect("volume overflows i64")
+}
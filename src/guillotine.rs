@@ -0,0 +1,175 @@
+/*
+ * Copyright 2019 Zejun Li
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Cutting trees of guillotine packings.
+//!
+//! A packing found with `Params::guillotine` can be cut out of the bin with edge-to-edge
+//! cuts. Its cutting tree lists them in stages: each stage cuts the pieces of the previous
+//! one across a single axis, starting across the height.
+
+#[cfg(feature = "serde")]
+use serde::*;
+
+use super::geom::{Cuboid, Point, Space};
+use super::Placement;
+
+/// The axis a cut goes across: `X` is the width, `Y` the height and `Z` the depth.
+#[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CutNode {
+    /// A piece holding the box `item_idx`.
+    Item {
+        space: Space,
+        item_idx: usize,
+    },
+    Waste {
+        space: Space,
+    },
+    /// Cuts across `axis` at `positions`, in ascending order, splitting `space` into the
+    /// `children`.
+    Cuts {
+        space: Space,
+        axis: Axis,
+        positions: Vec<i32>,
+        children: Vec<CutNode>,
+    },
+}
+
+impl CutNode {
+    pub fn space(&self) -> &Space {
+        match self {
+            CutNode::Item { space, .. }
+            | CutNode::Waste { space }
+            | CutNode::Cuts { space, .. } => space,
+        }
+    }
+
+    /// The number of stages of cuts below this piece.
+    pub fn stages(&self) -> usize {
+        match self {
+            CutNode::Cuts { children, .. } => {
+                1 + children.iter().map(CutNode::stages).max().unwrap_or(0)
+            }
+            _ => 0,
+        }
+    }
+}
+
+/// The cuts taking the placements of a bin out of it, `None` if some of them can not be
+/// reached with edge-to-edge cuts.
+pub fn cutting_tree(bin_spec: &Cuboid, placements: &[Placement]) -> Option<CutNode> {
+    let bin = Space::from_placement(&Point::new(0, 0, 0), bin_spec);
+    cut(bin, placements.iter().collect())
+}
+
+fn cut(space: Space, items: Vec<&Placement>) -> Option<CutNode> {
+    match items.as_slice() {
+        [] => return Some(CutNode::Waste { space }),
+        [p] if p.space == space => {
+            return Some(CutNode::Item {
+                space,
+                item_idx: p.item_idx,
+            })
+        }
+        _ => {}
+    }
+
+    for &axis in [Axis::Y, Axis::X, Axis::Z].iter() {
+        let positions = cut_positions(&space, &items, axis);
+        if positions.is_empty() {
+            continue;
+        }
+
+        let (low, high) = range(&space, axis);
+        let mut bounds = vec![low];
+        bounds.extend(positions.iter().cloned());
+        bounds.push(high);
+        let mut children = Vec::with_capacity(bounds.len() - 1);
+        for w in bounds.windows(2) {
+            let piece = slice(&space, axis, w[0], w[1]);
+            let inside = items
+                .iter()
+                .filter(|p| piece.contains(&p.space))
+                .cloned()
+                .collect();
+            children.push(cut(piece, inside)?);
+        }
+        return Some(CutNode::Cuts {
+            space,
+            axis,
+            positions,
+            children,
+        });
+    }
+    None
+}
+
+// the positions across `axis` which no item straddles, except the sides of `space`.
+fn cut_positions(space: &Space, items: &[&Placement], axis: Axis) -> Vec<i32> {
+    let mut ranges: Vec<(i32, i32)> = items.iter().map(|p| range(&p.space, axis)).collect();
+    ranges.sort_unstable();
+
+    let (low, high) = range(space, axis);
+    let mut positions = Vec::new();
+    let mut end = low;
+    for (start, stop) in ranges {
+        if start >= end {
+            positions.push(end);
+            positions.push(start);
+        }
+        end = end.max(stop);
+    }
+    positions.push(end);
+    positions.retain(|&p| p > low && p < high);
+    positions.dedup();
+    positions
+}
+
+fn range(space: &Space, axis: Axis) -> (i32, i32) {
+    let (b, u) = (&space.bottom_left, &space.upper_right);
+    match axis {
+        Axis::X => (b.x, u.x),
+        Axis::Y => (b.y, u.y),
+        Axis::Z => (b.z, u.z),
+    }
+}
+
+fn slice(space: &Space, axis: Axis, low: i32, high: i32) -> Space {
+    let (mut b, mut u) = (space.bottom_left, space.upper_right);
+    match axis {
+        Axis::X => {
+            b.x = low;
+            u.x = high;
+        }
+        Axis::Y => {
+            b.y = low;
+            u.y = high;
+        }
+        Axis::Z => {
+            b.z = low;
+            u.z = high;
+        }
+    }
+    Space::new(b, u)
+}
@@ -0,0 +1,287 @@
+/*
+ * Copyright 2019 Zejun Li
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Readers for the instance formats used in the bin packing literature.
+//!
+//! * `read_mpv`: the 3D instances of Martello, Pisinger and Vigo, as written by their
+//!   generator: `n W H D` on the first line, followed by `w h d` of each box.
+//! * `read_thpack`: the container loading instances of Bischoff and Ratcliff in the
+//!   OR-Library `thpack` files, which hold several problems each.
+//! * `read_2bp`: the 2D classes of Berkey and Wang and of Martello and Vigo, in the
+//!   `Class_XX.2bp` files of Lodi, Martello and Vigo, which hold several instances each.
+//!
+//! `generate_mpv` builds the 3D instances of Martello, Pisinger and Vigo without a file.
+
+use std::io::{self, BufRead, Read};
+
+use super::geom::{Cuboid, RotationType};
+
+#[derive(Clone, Debug)]
+pub struct Instance {
+    pub name: String,
+    pub bin: Cuboid,
+    pub boxes: Vec<Cuboid>,
+    /// The rotations the instance allows, as far as `RotationType` can express them.
+    pub rotation_type: RotationType,
+}
+
+pub fn read_mpv<R: Read>(name: &str, mut reader: R) -> io::Result<Instance> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    let mut tokens = Tokens::new(text.split_whitespace());
+
+    let n = tokens.next_usize()?;
+    let (w, h, d) = (tokens.next_i32()?, tokens.next_i32()?, tokens.next_i32()?);
+    let mut boxes = Vec::with_capacity(n);
+    for _ in 0..n {
+        let (w, h, d) = (tokens.next_i32()?, tokens.next_i32()?, tokens.next_i32()?);
+        boxes.push(Cuboid::new(w, d, h));
+    }
+
+    Ok(Instance {
+        name: name.to_string(),
+        bin: Cuboid::new(w, d, h),
+        boxes,
+        rotation_type: RotationType::ThreeDimension,
+    })
+}
+
+/// Each problem is named `<name>-<problem number>`. A box type lists for each of its
+/// dimensions whether it may be vertical, the first one that may is used as the height.
+/// Problems allowing all of them for every box are rotated freely, the others only around
+/// the vertical axis.
+pub fn read_thpack<R: Read>(name: &str, mut reader: R) -> io::Result<Vec<Instance>> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+    let mut tokens = Tokens::new(text.split_whitespace());
+
+    let num_problems = tokens.next_usize()?;
+    let mut instances = Vec::with_capacity(num_problems);
+    for _ in 0..num_problems {
+        let (number, _seed) = (tokens.next_usize()?, tokens.next_i32()?);
+        let (l, w, h) = (tokens.next_i32()?, tokens.next_i32()?, tokens.next_i32()?);
+        let num_types = tokens.next_usize()?;
+
+        let mut boxes = Vec::new();
+        let mut free_rotation = true;
+        for _ in 0..num_types {
+            let _type = tokens.next_usize()?;
+            let mut dimensions = [(0, false); 3];
+            for d in dimensions.iter_mut() {
+                *d = (tokens.next_i32()?, tokens.next_i32()? != 0);
+            }
+            let count = tokens.next_usize()?;
+
+            free_rotation &= dimensions.iter().all(|&(_, vertical)| vertical);
+            // prefer the original height, then the width and the length.
+            let vertical = (0..3)
+                .rev()
+                .find(|&i| dimensions[i].1)
+                .ok_or_else(|| invalid_data("a box type can not be placed at all"))?;
+            let (a, b) = ((vertical + 1) % 3, (vertical + 2) % 3);
+            let cuboid = Cuboid::new(dimensions[a].0, dimensions[b].0, dimensions[vertical].0);
+            boxes.extend((0..count).map(|_| cuboid));
+        }
+
+        instances.push(Instance {
+            name: format!("{}-{}", name, number),
+            bin: Cuboid::new(l, w, h),
+            boxes,
+            rotation_type: if free_rotation {
+                RotationType::ThreeDimension
+            } else {
+                RotationType::TwoDimension
+            },
+        });
+    }
+    Ok(instances)
+}
+
+/// Each instance is named `<name>-<absolute instance number>`. The rectangles lie in the
+/// plane of the width and the height with a depth of 1, which keeps their orientation fixed
+/// as in the original problem.
+pub fn read_2bp<R: BufRead>(name: &str, reader: R) -> io::Result<Vec<Instance>> {
+    // every line starts with its values, possibly followed by a description.
+    let mut lines = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        let values: Vec<i32> = line
+            .split_whitespace()
+            .map_while(|t| t.parse().ok())
+            .collect();
+        if !values.is_empty() {
+            lines.push(values);
+        }
+    }
+
+    let mut lines = lines.into_iter();
+    let mut next_line = |len: usize| match lines.next() {
+        Some(values) if values.len() >= len => Ok(values),
+        Some(_) => Err(invalid_data("too few values on a line")),
+        None => Err(invalid_data("unexpected end of file")),
+    };
+
+    let mut instances = Vec::new();
+    while let Ok(class) = next_line(1) {
+        let _class = class[0];
+        let n = next_line(1)?[0] as usize;
+        let number = next_line(2)?[1];
+        let bin = next_line(2)?;
+        let mut boxes = Vec::with_capacity(n);
+        for _ in 0..n {
+            let item = next_line(2)?;
+            boxes.push(Cuboid::new(item[1], 1, item[0]));
+        }
+
+        instances.push(Instance {
+            name: format!("{}-{}", name, number),
+            bin: Cuboid::new(bin[1], 1, bin[0]),
+            boxes,
+            rotation_type: RotationType::TwoDimension,
+        });
+    }
+    Ok(instances)
+}
+
+/// Generates an instance of the class `class` (1 to 8) of Martello, Pisinger and Vigo with
+/// `n` boxes, named `mpv<class>-<n>-<seed>`. The same seed always gives the same instance.
+///
+/// Classes 1 to 5 use a bin of 100 and mix five types of boxes: a box is of the type of the
+/// class with a probability of 60% and of each other type with 10%. Classes 6, 7 and 8 draw
+/// every dimension from `[1, 10]`, `[1, 35]` and `[1, 100]`, with bins of 10, 40 and 100.
+pub fn generate_mpv(class: u32, n: usize, seed: u64) -> Instance {
+    assert!((1..=8).contains(&class), "MPV classes are numbered 1 to 8");
+    let mut rng = SplitMix64(seed);
+
+    let (size, boxes) = match class {
+        6 => (10, (0..n).map(|_| uniform_box(&mut rng, 10)).collect()),
+        7 => (40, (0..n).map(|_| uniform_box(&mut rng, 35)).collect()),
+        8 => (100, (0..n).map(|_| uniform_box(&mut rng, 100)).collect()),
+        _ => {
+            let boxes = (0..n)
+                .map(|_| {
+                    let p = rng.range(1, 10);
+                    // types other than the class one take 10% each, in ascending order.
+                    let tp = if p <= 6 {
+                        class
+                    } else {
+                        let other = (p - 6) as u32;
+                        if other < class {
+                            other
+                        } else {
+                            other + 1
+                        }
+                    };
+                    typed_box(&mut rng, tp, 100)
+                })
+                .collect();
+            (100, boxes)
+        }
+    };
+
+    Instance {
+        name: format!("mpv{}-{}-{}", class, n, seed),
+        bin: Cuboid::new(size, size, size),
+        boxes,
+        rotation_type: RotationType::ThreeDimension,
+    }
+}
+
+fn uniform_box(rng: &mut SplitMix64, max: i32) -> Cuboid {
+    let (w, h, d) = (rng.range(1, max), rng.range(1, max), rng.range(1, max));
+    Cuboid::new(w, d, h)
+}
+
+// the five types of boxes of classes 1 to 5, each one small or large along given axes.
+fn typed_box(rng: &mut SplitMix64, tp: u32, size: i32) -> Cuboid {
+    let (half, two_thirds) = (size / 2, 2 * size / 3);
+    let small = |rng: &mut SplitMix64| rng.range(1, half);
+    let (w, h, d) = match tp {
+        1 => (
+            small(rng),
+            rng.range(two_thirds, size),
+            rng.range(two_thirds, size),
+        ),
+        2 => (
+            rng.range(two_thirds, size),
+            small(rng),
+            rng.range(two_thirds, size),
+        ),
+        3 => (
+            rng.range(two_thirds, size),
+            rng.range(two_thirds, size),
+            small(rng),
+        ),
+        4 => (
+            rng.range(half, size),
+            rng.range(half, size),
+            rng.range(half, size),
+        ),
+        _ => (small(rng), small(rng), small(rng)),
+    };
+    Cuboid::new(w, d, h)
+}
+
+// SplitMix64, so the instances only depend on the seed and not on the version of `rand`.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // uniform in `[low, high]`.
+    fn range(&mut self, low: i32, high: i32) -> i32 {
+        let span = (high - low + 1) as u64;
+        low + (self.next_u64() % span) as i32
+    }
+}
+
+struct Tokens<'a, I: Iterator<Item = &'a str>> {
+    inner: I,
+}
+
+impl<'a, I: Iterator<Item = &'a str>> Tokens<'a, I> {
+    fn new(inner: I) -> Self {
+        Tokens { inner }
+    }
+
+    fn next_i32(&mut self) -> io::Result<i32> {
+        let token = self
+            .inner
+            .next()
+            .ok_or_else(|| invalid_data("unexpected end of file"))?;
+        token
+            .parse()
+            .map_err(|_| invalid_data(&format!("not an integer: {}", token)))
+    }
+
+    fn next_usize(&mut self) -> io::Result<usize> {
+        let value = self.next_i32()?;
+        if value < 0 {
+            return Err(invalid_data(&format!("not a count: {}", value)));
+        }
+        Ok(value as usize)
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
@@ -0,0 +1,275 @@
+/*
+ * Copyright 2019 Zejun Li
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! A versioned JSON format for problems and solutions, described by the JSON Schemas in the
+//! `schema` directory of the repository.
+//!
+//! A problem lists its items with ids chosen by the caller, which the solution refers to:
+//!
+//! ```json
+//! {
+//!   "version": 2,
+//!   "bin": { "width": 100, "depth": 100, "height": 100 },
+//!   "items": [
+//!     { "id": "A-1", "width": 40, "depth": 30, "height": 20, "attributes": { "sku": 1042 } },
+//!     { "id": "A-2", "width": 40, "depth": 30, "height": 20 }
+//!   ],
+//!   "params": { "box_rotation_type": "TwoDimension" }
+//! }
+//! ```
+//!
+//! ```json
+//! {
+//!   "version": 2,
+//!   "bin": { "width": 100, "depth": 100, "height": 100 },
+//!   "rotation_type": "TwoDimension",
+//!   "bins": [
+//!     {
+//!       "placements": [
+//!         {
+//!           "id": "A-1",
+//!           "space": {
+//!             "bottom_left": { "x": 0, "y": 0, "z": 0 },
+//!             "upper_right": { "x": 40, "y": 20, "z": 30 }
+//!           }
+//!         }
+//!       ]
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! `params` and `attributes` may be left out. A solution also records the bin and the
+//! rotations the items were allowed, so it can be judged without the problem; version 1
+//! solutions lack both. Files written by a newer version of the format are rejected.
+
+use std::collections::{BTreeMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use serde::*;
+use serde_json::Value;
+
+use super::geom::{Cuboid, RotationType, Space};
+use super::{pack_identified, FitError, Identified, IdentifiedSolution, Params};
+
+/// The version written by this crate, the newest one it can read.
+pub const FORMAT_VERSION: u32 = 2;
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Problem {
+    pub version: u32,
+    pub bin: Cuboid,
+    pub items: Vec<Item>,
+    #[serde(default)]
+    pub params: Params,
+}
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Item {
+    pub id: String,
+    pub width: i32,
+    pub depth: i32,
+    pub height: i32,
+    /// Anything the caller wants to keep with the item, the packer ignores it.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: BTreeMap<String, Value>,
+}
+
+impl From<&Item> for Cuboid {
+    fn from(item: &Item) -> Self {
+        Cuboid::new(item.width, item.depth, item.height)
+    }
+}
+
+impl Identified for Item {
+    type Id = String;
+
+    fn id(&self) -> String {
+        self.id.clone()
+    }
+}
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Solution {
+    pub version: u32,
+    /// `None` in version 1 solutions.
+    #[serde(default)]
+    pub bin: Option<Cuboid>,
+    #[serde(default)]
+    pub rotation_type: Option<RotationType>,
+    pub bins: Vec<SolutionBin>,
+}
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct SolutionBin {
+    pub placements: Vec<ItemPlacement>,
+}
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct ItemPlacement {
+    pub id: String,
+    pub space: Space,
+}
+
+#[derive(Debug)]
+pub enum FormatError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    UnsupportedVersion(u32),
+    DuplicateId(String),
+    Fit(FitError),
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FormatError::Io(err) => write!(f, "{}", err),
+            FormatError::Json(err) => write!(f, "{}", err),
+            FormatError::UnsupportedVersion(v) => write!(
+                f,
+                "format version {} is newer than the supported version {}",
+                v, FORMAT_VERSION
+            ),
+            FormatError::DuplicateId(id) => write!(f, "duplicate item id {}", id),
+            FormatError::Fit(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for FormatError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FormatError::Io(err) => Some(err),
+            FormatError::Json(err) => Some(err),
+            FormatError::Fit(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for FormatError {
+    fn from(err: io::Error) -> Self {
+        FormatError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for FormatError {
+    fn from(err: serde_json::Error) -> Self {
+        FormatError::Json(err)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, FormatError>;
+
+impl Problem {
+    pub fn new(bin: Cuboid, items: Vec<Item>, params: Params) -> Self {
+        Problem {
+            version: FORMAT_VERSION,
+            bin,
+            items,
+            params,
+        }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.to_writer(BufWriter::new(File::create(path)?))
+    }
+
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self> {
+        let problem: Problem = serde_json::from_reader(reader)?;
+        check_version(problem.version)?;
+        let spacing = problem.params.spacing;
+        if spacing.clearance < 0 || spacing.margin < 0 {
+            return Err(FormatError::Fit(FitError::NegativeSpacing(spacing)));
+        }
+        let mut ids = HashSet::new();
+        for item in &problem.items {
+            if !ids.insert(&item.id) {
+                return Err(FormatError::DuplicateId(item.id.clone()));
+            }
+        }
+        Ok(problem)
+    }
+
+    pub fn to_writer<W: Write>(&self, writer: W) -> Result<()> {
+        Ok(serde_json::to_writer_pretty(writer, self)?)
+    }
+
+    pub fn solve(&self) -> Solution {
+        let bins = pack_identified(self.params, self.bin, &self.items);
+        Solution::new(self.bin, self.params.box_rotation_type, &bins)
+    }
+}
+
+impl Solution {
+    pub fn new(
+        bin: Cuboid,
+        rotation_type: RotationType,
+        bins: &IdentifiedSolution<String>,
+    ) -> Self {
+        let bins = bins
+            .iter()
+            .map(|bin| SolutionBin {
+                placements: bin
+                    .iter()
+                    .map(|p| ItemPlacement {
+                        id: p.id.clone(),
+                        space: p.space,
+                    })
+                    .collect(),
+            })
+            .collect();
+        Solution {
+            version: FORMAT_VERSION,
+            bin: Some(bin),
+            rotation_type: Some(rotation_type),
+            bins,
+        }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.to_writer(BufWriter::new(File::create(path)?))
+    }
+
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self> {
+        let solution: Solution = serde_json::from_reader(reader)?;
+        check_version(solution.version)?;
+        Ok(solution)
+    }
+
+    pub fn to_writer<W: Write>(&self, writer: W) -> Result<()> {
+        Ok(serde_json::to_writer_pretty(writer, self)?)
+    }
+}
+
+fn check_version(version: u32) -> Result<()> {
+    if version > FORMAT_VERSION {
+        return Err(FormatError::UnsupportedVersion(version));
+    }
+    Ok(())
+}
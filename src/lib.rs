@@ -14,20 +14,130 @@
  * limitations under the License.
  */
 
+pub mod bound;
 pub mod geom;
+pub mod guillotine;
+pub mod instances;
+#[cfg(feature = "json")]
+pub mod json;
+pub mod objective;
+pub mod pallet;
+pub mod units;
 
+mod clock;
+mod ems;
+mod exact;
 mod ga;
 mod placer;
 
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::hash::Hash;
+use std::time::Duration;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 #[cfg(feature = "serde")]
 use serde::*;
 
-use self::ga::{RandGenerator, Solver};
-use self::geom::{Cuboid, RotationType, Space};
-use self::placer::Decoder;
+use self::bound::{lower_bound_of_types, LowerBound};
+use self::clock::Instant;
+use self::exact::ExactSolver;
+use self::ga::{Decoder as GADecoder, RandGenerator, Solver};
+use self::geom::{Cuboid, Point, Rect, RotationType, Space};
+use self::objective::Objective;
+use self::placer::{rotate_cuboid, Decoder, InnerBox, InnerSolution};
+use self::units::{ConversionError, Dimensions, UnitPlacement, Units, UnitsError};
+
+/// How the boxes of a type are laid out in the empty space chosen for them.
+#[derive(PartialEq, Copy, Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Layout {
+    #[default]
+    /// In the orientation given by the chromosome, filling the width first, then the depth
+    /// and the height.
+    Fill,
+    /// As a block chosen by the chromosome among the largest blocks of every orientation,
+    /// filling the axes in every order: layers on the floor, walls and columns. Identical
+    /// boxes are packed as a single type.
+    Blocks,
+}
+
+/// Gaps kept free around the boxes, e.g. for dunnage or forklift clearance. Boxes are packed
+/// as if they were larger by `clearance` in every dimension, and the placements give their
+/// actual size.
+#[derive(PartialEq, Copy, Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Spacing {
+    /// The gap between two boxes, also between boxes stacked on each other.
+    pub clearance: i32,
+    /// The gap between the boxes and the four walls of the bin, the boxes still stand on its
+    /// floor.
+    pub margin: i32,
+}
+
+impl Spacing {
+    // the bin the padded boxes are packed in.
+    fn pad_bin(&self, bin_spec: Cuboid) -> Cuboid {
+        let (c, m) = (self.clearance, self.margin);
+        Cuboid::new(
+            bin_spec.width - 2 * m + c,
+            bin_spec.depth - 2 * m + c,
+            bin_spec.height + c,
+        )
+    }
+
+    fn pad_box(&self, cuboid: Cuboid) -> Cuboid {
+        let c = self.clearance;
+        Cuboid::new(cuboid.width + c, cuboid.depth + c, cuboid.height + c)
+    }
+
+    // the actual space of a box packed with padding.
+    fn unpad(&self, space: &Space) -> Space {
+        let (c, m) = (self.clearance, self.margin);
+        let b = &space.bottom_left;
+        let origin = Point::new(b.x + m, b.y, b.z + m);
+        let size = Cuboid::new(space.width() - c, space.depth() - c, space.height() - c);
+        Space::from_placement(&origin, &size)
+    }
+}
+
+/// A problem which can not be packed, as the spacing is negative, the margins take up the
+/// whole bin or a box does not fit in it in any allowed orientation once the spacing is added.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum FitError {
+    NegativeSpacing(Spacing),
+    Margin(i32),
+    Oversize { item_idx: usize, cuboid: Cuboid },
+}
+
+impl fmt::Display for FitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FitError::NegativeSpacing(spacing) => write!(
+                f,
+                "a clearance of {} and a margin of {} must not be negative",
+                spacing.clearance, spacing.margin
+            ),
+            FitError::Margin(margin) => {
+                write!(f, "a margin of {} leaves no room in the bin", margin)
+            }
+            FitError::Oversize { item_idx, cuboid } => write!(
+                f,
+                "box {} ({}x{}x{}) does not fit in the bin with the clearance and margin",
+                item_idx, cuboid.width, cuboid.depth, cuboid.height
+            ),
+        }
+    }
+}
+
+impl Error for FitError {}
 
 #[derive(PartialEq, Copy, Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Params {
     pub population_factor: usize,
     pub elites_percentage: f64,
@@ -36,6 +146,28 @@ pub struct Params {
     pub max_generations: i32,
     pub max_generations_no_improvement: i32,
     pub box_rotation_type: RotationType,
+    /// Problems with at most this many boxes are solved with the exact solver first, which
+    /// skips the genetic algorithm when it meets the lower bound. Off by default: the search
+    /// can take up to a million nodes, far longer than the genetic algorithm on most small
+    /// problems.
+    pub exact_threshold: usize,
+    pub objective: Objective,
+    pub layout: Layout,
+    /// Only pack boxes which can be taken out of the bin with edge-to-edge cuts, see
+    /// `guillotine::cutting_tree`.
+    pub guillotine: bool,
+    pub spacing: Spacing,
+    /// Stop searching and evolving once this much time has passed, the best solution so far is
+    /// returned.
+    pub time_limit: Option<Duration>,
+    /// The number of threads decoding a generation with the `rayon` feature: 1 decodes on the
+    /// calling thread, 0 uses the current rayon pool. That is the global pool, unless the
+    /// packer is called inside `ThreadPool::install` to keep it apart from other work.
+    pub threads: usize,
+    /// Seeds the random numbers, so the same problem and parameters always give the same
+    /// solution unless the time limit cuts the evolution short. A random seed is drawn when
+    /// `None`.
+    pub seed: Option<u64>,
 }
 
 impl Default for Params {
@@ -48,15 +180,65 @@ impl Default for Params {
             max_generations: 200,
             max_generations_no_improvement: 5,
             box_rotation_type: RotationType::ThreeDimension,
+            exact_threshold: 0,
+            objective: Objective::default(),
+            layout: Layout::default(),
+            guillotine: false,
+            spacing: Spacing::default(),
+            time_limit: None,
+            threads: 0,
+            seed: None,
         }
     }
 }
 
 impl Params {
+    /// Checks that every box fits in the bin in some allowed orientation with the spacing,
+    /// which the packing functions assume and panic otherwise.
+    pub fn check_fit<'a, T>(&self, bin_spec: Cuboid, boxes: &'a [T]) -> Result<(), FitError>
+    where
+        &'a T: Into<Cuboid>,
+    {
+        if self.spacing.clearance < 0 || self.spacing.margin < 0 {
+            return Err(FitError::NegativeSpacing(self.spacing));
+        }
+        let margin = self.spacing.margin;
+        if 2 * margin >= bin_spec.width || 2 * margin >= bin_spec.depth {
+            return Err(FitError::Margin(margin));
+        }
+        let bin = Space::from_placement(&Point::new(0, 0, 0), &self.spacing.pad_bin(bin_spec));
+        let mut orientations = Vec::new();
+        for (item_idx, b) in boxes.iter().enumerate() {
+            let cuboid = b.into();
+            orientations.clear();
+            rotate_cuboid(
+                self.box_rotation_type,
+                &self.spacing.pad_box(cuboid),
+                &mut orientations,
+            );
+            if !orientations.iter().any(|o| o.can_fit_in(&bin)) {
+                return Err(FitError::Oversize { item_idx, cuboid });
+            }
+        }
+        Ok(())
+    }
+
+    fn assert_fit<'a, T>(&self, bin_spec: Cuboid, boxes: &'a [T])
+    where
+        &'a T: Into<Cuboid>,
+    {
+        if let Err(e) = self.check_fit(bin_spec, boxes) {
+            panic!("{}", e);
+        }
+    }
+
     fn get_ga_params(&self, num_items: usize) -> ga::Params {
-        let population_size = self.population_factor * num_items;
+        // crossover picks one elite and one non-elite parent, so keep at least one of each.
+        let population_size = (self.population_factor * num_items).max(2);
         let num_elites = (self.elites_percentage * population_size as f64) as usize;
+        let num_elites = num_elites.clamp(1, population_size - 1);
         let num_mutants = (self.mutants_percentage * population_size as f64) as usize;
+        let num_mutants = num_mutants.min(population_size - num_elites);
         ga::Params {
             population_size,
             num_elites,
@@ -64,6 +246,9 @@ impl Params {
             inherit_elite_probability: self.inherit_elite_probability,
             max_generations: self.max_generations,
             max_generations_no_improvement: self.max_generations_no_improvement,
+            time_limit: self.time_limit,
+            parallel: true,
+            seed: self.seed.unwrap_or_else(rand::random),
         }
     }
 }
@@ -77,39 +262,641 @@ pub struct Placement {
 
 pub type PackSolution = Vec<Vec<Placement>>;
 
-macro_rules! do_pack {
-    ($params:ident, $bin_spec:ident, $boxes:ident) => {{
-        let generator = RandGenerator::new($boxes.len() * 2);
-        let ga_params = $params.get_ga_params($boxes.len());
-        let mut solver = Solver::new(ga_params, generator, || {
-            Decoder::new($boxes, $bin_spec, $params.box_rotation_type)
-        });
-        let solution = solver.solve();
+#[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Placement2D {
+    pub x: i32,
+    pub y: i32,
+    /// The rectangle as placed, its width and height are swapped if it is rotated.
+    pub rect: Rect,
+    pub rotated: bool,
+    pub item_idx: usize,
+}
+
+/// `quantity` identical boxes.
+#[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BoxType {
+    pub cuboid: Cuboid,
+    pub quantity: usize,
+}
+
+impl BoxType {
+    pub fn new(cuboid: Cuboid, quantity: usize) -> Self {
+        BoxType { cuboid, quantity }
+    }
+}
+
+impl From<&BoxType> for BoxType {
+    fn from(box_type: &BoxType) -> Self {
+        *box_type
+    }
+}
+
+impl From<&InnerBox> for BoxType {
+    fn from(inner: &InnerBox) -> Self {
+        BoxType::new(inner.cuboid, inner.quantity)
+    }
+}
+
+/// An item carrying an id chosen by the caller, e.g. its SKU and order line, which is
+/// returned in its placement.
+pub trait Identified {
+    type Id: Clone;
+
+    fn id(&self) -> Self::Id;
+}
+
+impl<I: Clone> Identified for (Cuboid, I) {
+    type Id = I;
+
+    fn id(&self) -> I {
+        self.1.clone()
+    }
+}
+
+impl<I> From<&(Cuboid, I)> for Cuboid {
+    fn from(item: &(Cuboid, I)) -> Self {
+        item.0
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct IdentifiedPlacement<I> {
+    pub space: Space,
+    pub id: I,
+}
+
+pub type IdentifiedSolution<I> = Vec<Vec<IdentifiedPlacement<I>>>;
+
+#[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Quantity<I> {
+    pub id: I,
+    pub quantity: usize,
+}
+
+/// Groups the placements of each bin by id, in the order each id first appears in the bin.
+pub fn group_quantities<I>(solution: &IdentifiedSolution<I>) -> Vec<Vec<Quantity<I>>>
+where
+    I: Eq + Hash + Clone,
+{
+    solution
+        .iter()
+        .map(|bin| {
+            let mut quantities: Vec<Quantity<I>> = Vec::new();
+            let mut positions = HashMap::new();
+            for p in bin {
+                let pos = *positions.entry(&p.id).or_insert_with(|| {
+                    quantities.push(Quantity {
+                        id: p.id.clone(),
+                        quantity: 0,
+                    });
+                    quantities.len() - 1
+                });
+                quantities[pos].quantity += 1;
+            }
+            quantities
+        })
+        .collect()
+}
+
+#[derive(PartialEq, Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PackReport {
+    pub bins: PackSolution,
+    /// The fraction of each bin's volume used by its boxes.
+    pub utilization: Vec<f64>,
+    pub overall_utilization: f64,
+    pub fitness: f64,
+    pub generations: usize,
+    pub num_decoded: usize,
+    pub elapsed: Duration,
+    pub lower_bound: usize,
+    pub gap: f64,
+    /// The number of bins is proven to be optimal.
+    pub optimal: bool,
+}
+
+impl PackReport {
+    fn new(
+        solution: &InnerSolution,
+        bin_spec: &Cuboid,
+        spacing: &Spacing,
+        bound: &LowerBound,
+    ) -> Self {
+        let bins = into_bins(solution, spacing);
+        let bin_volume = bin_spec.volume() as f64;
+        let utilization: Vec<f64> = bins
+            .iter()
+            .map(|bin| {
+                let used: i64 = bin.iter().map(|p| p.space.volume()).sum();
+                used as f64 / bin_volume
+            })
+            .collect();
+        let overall_utilization = if bins.is_empty() {
+            0.
+        } else {
+            utilization.iter().sum::<f64>() / bins.len() as f64
+        };
+
+        PackReport {
+            utilization,
+            overall_utilization,
+            fitness: 0.,
+            generations: 0,
+            num_decoded: 0,
+            elapsed: Duration::from_secs(0),
+            lower_bound: bound.value(),
+            gap: bound.gap(bins.len()),
+            optimal: bins.len() <= bound.value(),
+            bins,
+        }
+    }
+}
+
+fn into_bins(solution: &InnerSolution, spacing: &Spacing) -> PackSolution {
+    let mut bins = vec![Vec::new(); solution.num_bins];
+    for inner_placement in &solution.placements {
+        let idx = inner_placement.bin_no;
+        let space = spacing.unpad(&inner_placement.space);
+        let item_idx = inner_placement.box_idx;
+        bins[idx].push(Placement { space, item_idx })
+    }
+    bins
+}
+
+// `parallel` decodes the individuals of a generation in parallel, when built with rayon.
+fn pack(params: Params, bin_spec: Cuboid, boxes: Vec<InnerBox>, parallel: bool) -> PackReport {
+    let start = Instant::now();
+    let spacing = params.spacing;
+    let padded_spec = spacing.pad_bin(bin_spec);
+    let boxes: Vec<InnerBox> = boxes
+        .into_iter()
+        .map(|b| InnerBox::new(spacing.pad_box(b.cuboid), b.quantity))
+        .collect();
+    let bound = lower_bound_of_types(params.box_rotation_type, padded_spec, &boxes);
+    let decoder_factory = || {
+        Decoder::new(
+            boxes.clone(),
+            padded_spec,
+            params.box_rotation_type,
+            params.objective,
+            params.layout,
+            params.guillotine,
+            bound.value(),
+        )
+    };
+    let (mut generations, mut num_decoded) = (0, 0);
+    // the number of bins the exact solver proved optimal.
+    let (mut solution, mut proven_bins) = (None, None);
+    let num_boxes: usize = boxes.iter().map(|b| b.quantity).sum();
+    if num_boxes <= params.exact_threshold {
+        // the exact solver handles every box on its own.
+        let mut owners = Vec::with_capacity(num_boxes);
+        let mut singles = Vec::with_capacity(num_boxes);
+        for (idx, b) in boxes.iter().enumerate() {
+            owners.extend((0..b.quantity).map(|_| idx));
+            singles.extend((0..b.quantity).map(|_| InnerBox::new(b.cuboid, 1)));
+        }
+        let solver = ExactSolver::new(
+            singles,
+            padded_spec,
+            params.box_rotation_type,
+            params.guillotine,
+            bound.value(),
+            params
+                .time_limit
+                .map(|limit| limit.checked_sub(start.elapsed()).unwrap_or_default()),
+        );
+        if let Some((mut s, p)) = solver.solve() {
+            for placement in &mut s.placements {
+                placement.box_idx = owners[placement.box_idx];
+            }
+            if p {
+                proven_bins = Some(s.num_bins);
+            }
+            solution = Some(s);
+        }
+    }
 
-        let mut bins = vec![Vec::new(); solution.num_bins];
-        for inner_placement in &solution.placements {
-            let idx = inner_placement.bin_no;
-            let space = inner_placement.space;
-            let item_idx = inner_placement.box_idx;
-            bins[idx].push(Placement { space, item_idx })
+    // fall back to the genetic algorithm unless the exact solver proved its number of bins.
+    let done = solution
+        .as_ref()
+        .is_some_and(|s| proven_bins.is_some() || s.num_bins <= bound.value());
+    if !done {
+        let decoder = decoder_factory();
+        let generator = RandGenerator::new(boxes.len() * 2);
+        let mut ga_params = params.get_ga_params(boxes.len());
+        ga_params.time_limit = params
+            .time_limit
+            .map(|limit| limit.checked_sub(start.elapsed()).unwrap_or_default());
+        ga_params.parallel = parallel && params.threads != 1;
+        let mut solver = Solver::new(ga_params, generator, decoder_factory);
+        solver.seed(decoder.greedy_chromosome());
+        if let Some(s) = &solution {
+            solver.seed(decoder.chromosome_of(s));
         }
-        bins
-    }};
+        // a sequential run decodes on the calling thread, so it needs no pool of its own.
+        let evolved = if ga_params.parallel {
+            with_threads(params.threads, || solver.solve())
+        } else {
+            solver.solve()
+        };
+        generations = solver.generations();
+        num_decoded = solver.num_decoded();
+        if solution
+            .as_ref()
+            .is_none_or(|s| decoder.fitness_of(&evolved) < decoder.fitness_of(s))
+        {
+            solution = Some(evolved);
+        }
+    }
+
+    let solution = solution.unwrap();
+    let mut report = PackReport::new(&solution, &bin_spec, &spacing, &bound);
+    report.optimal |= proven_bins == Some(solution.num_bins);
+    report.fitness = decoder_factory().fitness_of(&solution);
+    report.generations = generations;
+    report.num_decoded = num_decoded;
+    report.elapsed = start.elapsed();
+    report
 }
 
+// run `f` on a pool of `threads` threads, or on the current one when `threads` is at most 1
+// or the current pool has as many.
 #[cfg(feature = "rayon")]
-pub fn pack_boxes<'a, T>(params: Params, bin_spec: Cuboid, boxes: &'a [T]) -> PackSolution
+fn with_threads<R, F>(threads: usize, f: F) -> R
 where
-    T: Sync,
-    &'a T: Into<Cuboid>,
+    R: Send,
+    F: FnOnce() -> R + Send,
 {
-    do_pack!(params, bin_spec, boxes)
+    if threads > 1 && threads != rayon::current_num_threads() {
+        if let Ok(pool) = rayon::ThreadPoolBuilder::new().num_threads(threads).build() {
+            return pool.install(f);
+        }
+    }
+    f()
 }
 
 #[cfg(not(feature = "rayon"))]
+fn with_threads<R, F>(_threads: usize, f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    f()
+}
+
 pub fn pack_boxes<'a, T>(params: Params, bin_spec: Cuboid, boxes: &'a [T]) -> PackSolution
 where
     &'a T: Into<Cuboid>,
 {
-    do_pack!(params, bin_spec, boxes)
+    pack_boxes_with_report(params, bin_spec, boxes).bins
+}
+
+pub fn pack_boxes_with_report<'a, T>(params: Params, bin_spec: Cuboid, boxes: &'a [T]) -> PackReport
+where
+    &'a T: Into<Cuboid>,
+{
+    let cuboids: Vec<Cuboid> = boxes.iter().map(|b| b.into()).collect();
+    pack_cuboids(params, bin_spec, &cuboids, true)
+}
+
+/// Like `pack_boxes_with_report`, decoding on `pool` instead of `params.threads`, so one pool
+/// can be built and shared by many calls.
+#[cfg(feature = "rayon")]
+pub fn pack_boxes_in<'a, T>(
+    pool: &rayon::ThreadPool,
+    params: Params,
+    bin_spec: Cuboid,
+    boxes: &'a [T],
+) -> PackReport
+where
+    &'a T: Into<Cuboid>,
+{
+    let cuboids: Vec<Cuboid> = boxes.iter().map(|b| b.into()).collect();
+    let params = Params {
+        threads: 0,
+        ..params
+    };
+    pool.install(|| pack_cuboids(params, bin_spec, &cuboids, true))
+}
+
+fn pack_cuboids(
+    params: Params,
+    bin_spec: Cuboid,
+    cuboids: &[Cuboid],
+    parallel: bool,
+) -> PackReport {
+    params.assert_fit(bin_spec, cuboids);
+    if params.layout == Layout::Blocks {
+        return pack_grouped(params, bin_spec, cuboids, parallel);
+    }
+    let boxes = cuboids.iter().map(InnerBox::from).collect();
+    pack(params, bin_spec, boxes, parallel)
+}
+
+// identical boxes are packed as one type, then given back their own indices.
+fn pack_grouped(
+    params: Params,
+    bin_spec: Cuboid,
+    cuboids: &[Cuboid],
+    parallel: bool,
+) -> PackReport {
+    let mut types: Vec<InnerBox> = Vec::new();
+    let mut members: Vec<Vec<usize>> = Vec::new();
+    for (idx, cuboid) in cuboids.iter().enumerate() {
+        match types.iter().position(|t| t.cuboid == *cuboid) {
+            Some(t) => {
+                types[t].quantity += 1;
+                members[t].push(idx);
+            }
+            None => {
+                types.push(InnerBox::new(*cuboid, 1));
+                members.push(vec![idx]);
+            }
+        }
+    }
+
+    let mut report = pack(params, bin_spec, types, parallel);
+    let mut next = vec![0; members.len()];
+    for placement in report.bins.iter_mut().flatten() {
+        let t = placement.item_idx;
+        placement.item_idx = members[t][next[t]];
+        next[t] += 1;
+    }
+    report
+}
+
+/// Packs `quantity` boxes of each type, `Placement::item_idx` being the index of the type.
+/// Identical boxes share a single gene and are placed in blocks.
+pub fn pack_box_types<'a, T>(params: Params, bin_spec: Cuboid, types: &'a [T]) -> PackSolution
+where
+    &'a T: Into<BoxType>,
+{
+    pack_box_types_with_report(params, bin_spec, types).bins
+}
+
+pub fn pack_box_types_with_report<'a, T>(
+    params: Params,
+    bin_spec: Cuboid,
+    types: &'a [T],
+) -> PackReport
+where
+    &'a T: Into<BoxType>,
+{
+    let types: Vec<BoxType> = types.iter().map(|t| t.into()).collect();
+    let cuboids: Vec<Cuboid> = types.iter().map(|t| t.cuboid).collect();
+    params.assert_fit(bin_spec, &cuboids);
+    let boxes = types
+        .iter()
+        .map(|t| InnerBox::new(t.cuboid, t.quantity))
+        .collect();
+    pack(params, bin_spec, boxes, true)
+}
+
+pub fn pack_identified<'a, T>(
+    params: Params,
+    bin_spec: Cuboid,
+    items: &'a [T],
+) -> IdentifiedSolution<T::Id>
+where
+    T: Identified,
+    &'a T: Into<Cuboid>,
+{
+    identify(&pack_boxes(params, bin_spec, items), items)
+}
+
+fn identify<T: Identified>(bins: &PackSolution, items: &[T]) -> IdentifiedSolution<T::Id> {
+    bins.iter()
+        .map(|bin| {
+            bin.iter()
+                .map(|p| IdentifiedPlacement {
+                    space: p.space,
+                    id: items[p.item_idx].id(),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// A problem of a batch.
+#[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Job {
+    pub params: Params,
+    pub bin_spec: Cuboid,
+    pub boxes: Vec<Cuboid>,
+}
+
+// jobs with fewer boxes run side by side on a thread each, the bigger ones run one after the
+// other with every thread decoding their generations.
+const SMALL_JOB: usize = 200;
+
+/// Packs every job within `time_budget` overall and returns their reports in order, or the
+/// error of `Params::check_fit` for a job which can not be packed. Each job may take a share
+/// of the budget in proportion to its number of boxes, and never more than is left of it.
+pub fn pack_batch(jobs: &[Job], time_budget: Duration) -> Vec<Result<PackReport, FitError>> {
+    let start = Instant::now();
+    let total_boxes = jobs.iter().map(|j| j.boxes.len()).sum::<usize>().max(1);
+    let run = |job: &Job, parallel: bool| {
+        job.params.check_fit(job.bin_spec, &job.boxes)?;
+        let share = time_budget.mul_f64(job.boxes.len() as f64 / total_boxes as f64);
+        let left = time_budget.checked_sub(start.elapsed()).unwrap_or_default();
+        let limit = job
+            .params
+            .time_limit
+            .map_or(share, |l| l.min(share))
+            .min(left);
+        let params = Params {
+            time_limit: Some(limit),
+            ..job.params
+        };
+        Ok(pack_cuboids(params, job.bin_spec, &job.boxes, parallel))
+    };
+
+    let (big, small): (Vec<usize>, Vec<usize>) =
+        (0..jobs.len()).partition(|&i| jobs[i].boxes.len() >= SMALL_JOB);
+    let mut reports = vec![None; jobs.len()];
+    // the big jobs share one pool for each number of threads.
+    #[cfg(feature = "rayon")]
+    let mut pools = HashMap::new();
+    for i in big {
+        #[cfg(feature = "rayon")]
+        {
+            let threads = jobs[i].params.threads;
+            let pool = pools.entry(threads).or_insert_with(|| match threads {
+                0 | 1 => None,
+                _ => rayon::ThreadPoolBuilder::new()
+                    .num_threads(threads)
+                    .build()
+                    .ok(),
+            });
+            if let Some(pool) = pool {
+                reports[i] = Some(pool.install(|| run(&jobs[i], true)));
+                continue;
+            }
+        }
+        reports[i] = Some(run(&jobs[i], true));
+    }
+
+    #[cfg(feature = "rayon")]
+    let packed: Vec<_> = small.par_iter().map(|&i| run(&jobs[i], false)).collect();
+    #[cfg(not(feature = "rayon"))]
+    let packed: Vec<_> = small.iter().map(|&i| run(&jobs[i], false)).collect();
+    for (i, report) in small.into_iter().zip(packed) {
+        reports[i] = Some(report);
+    }
+    reports.into_iter().map(Option::unwrap).collect()
+}
+
+pub fn pack_boxes_exact<'a, T>(params: Params, bin_spec: Cuboid, boxes: &'a [T]) -> PackSolution
+where
+    &'a T: Into<Cuboid>,
+{
+    params.assert_fit(bin_spec, boxes);
+    let spacing = params.spacing;
+    let padded_spec = spacing.pad_bin(bin_spec);
+    let boxes: Vec<InnerBox> = boxes
+        .iter()
+        .map(|b| spacing.pad_box(b.into()).into())
+        .collect();
+    let bound = lower_bound_of_types(params.box_rotation_type, padded_spec, &boxes);
+    let solver = ExactSolver::new(
+        boxes,
+        padded_spec,
+        params.box_rotation_type,
+        params.guillotine,
+        bound.value(),
+        params.time_limit,
+    );
+    let (solution, _) = solver.solve().expect("every box must fit in the bin");
+    into_bins(&solution, &spacing)
+}
+
+pub fn pack_boxes_greedy<'a, T>(params: Params, bin_spec: Cuboid, boxes: &'a [T]) -> PackSolution
+where
+    &'a T: Into<Cuboid>,
+{
+    params.assert_fit(bin_spec, boxes);
+    let spacing = params.spacing;
+    let mut decoder = Decoder::new(
+        boxes
+            .iter()
+            .map(|b| spacing.pad_box(b.into()).into())
+            .collect(),
+        spacing.pad_bin(bin_spec),
+        params.box_rotation_type,
+        params.objective,
+        params.layout,
+        params.guillotine,
+        0,
+    );
+    let chromosome = decoder.greedy_chromosome();
+    into_bins(&decoder.materialize(&chromosome), &spacing)
+}
+
+/// Packs rectangles in the plane of the width and the height, as cuboids of depth 1 whose
+/// empty maximal spaces are the maximal rectangles. A rectangle may be rotated by 90° unless
+/// `params.box_rotation_type` is `TwoDimension`, which only turns boxes around the vertical
+/// axis. `params.spacing` keeps its margin from the four edges of the bin.
+pub fn pack_rectangles<'a, T>(
+    params: Params,
+    bin_spec: Rect,
+    items: &'a [T],
+) -> Vec<Vec<Placement2D>>
+where
+    &'a T: Into<Rect>,
+{
+    // pad in the plane only, the depth of 1 is left alone.
+    let Spacing { clearance, margin } = params.spacing;
+    let params = Params {
+        spacing: Spacing::default(),
+        ..params
+    };
+    let rects: Vec<Rect> = items.iter().map(|r| r.into()).collect();
+    let padded: Vec<Rect> = rects
+        .iter()
+        .map(|r| Rect::new(r.width + clearance, r.height + clearance))
+        .collect();
+    if clearance < 0 || margin < 0 {
+        panic!(
+            "{}",
+            FitError::NegativeSpacing(Spacing { clearance, margin })
+        );
+    }
+    if 2 * margin >= bin_spec.width || 2 * margin >= bin_spec.height {
+        panic!("{}", FitError::Margin(margin));
+    }
+    let padded_bin = Rect::new(
+        bin_spec.width - 2 * margin + clearance,
+        bin_spec.height - 2 * margin + clearance,
+    );
+    let rotatable = params.box_rotation_type == RotationType::ThreeDimension;
+    for (item_idx, r) in padded.iter().enumerate() {
+        let fits = |w, h| w <= padded_bin.width && h <= padded_bin.height;
+        if !(fits(r.width, r.height) || rotatable && fits(r.height, r.width)) {
+            let cuboid = (&rects[item_idx]).into();
+            panic!("{}", FitError::Oversize { item_idx, cuboid });
+        }
+    }
+    pack_boxes(params, (&padded_bin).into(), &padded)
+        .iter()
+        .map(|bin| {
+            bin.iter()
+                .map(|p| {
+                    let item = &rects[p.item_idx];
+                    let rect = Rect::new(p.space.width() - clearance, p.space.height() - clearance);
+                    Placement2D {
+                        x: p.space.bottom_left.x + margin,
+                        y: p.space.bottom_left.y + margin,
+                        rect,
+                        rotated: rect != *item,
+                        item_idx: p.item_idx,
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Packs boxes with fractional dimensions, which are put on the integer grid by `units`. The
+/// placements are in the caller's units and cover the rounded up size of each box, while
+/// `params.spacing` is in grid steps. Fails when a dimension has no grid value or a rounded
+/// box does not fit in the rounded bin.
+pub fn pack_boxes_in_units<'a, T>(
+    params: Params,
+    units: Units,
+    bin_spec: Dimensions,
+    boxes: &'a [T],
+) -> Result<Vec<Vec<UnitPlacement>>, UnitsError>
+where
+    &'a T: Into<Dimensions>,
+{
+    let bin = units.bin_to_grid(&bin_spec)?;
+    let cuboids = boxes
+        .iter()
+        .enumerate()
+        .map(|(idx, b)| {
+            units.box_to_grid(&b.into()).map_err(|err| ConversionError {
+                item_idx: Some(idx),
+                ..err
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    params.check_fit(bin, &cuboids)?;
+
+    let bins = pack_boxes(params, bin, &cuboids)
+        .iter()
+        .map(|bin| {
+            bin.iter()
+                .map(|p| UnitPlacement {
+                    region: units.space_from_grid(&p.space),
+                    item_idx: p.item_idx,
+                })
+                .collect()
+        })
+        .collect();
+    Ok(bins)
 }
@@ -0,0 +1,76 @@
+/*
+ * Copyright 2019 Zejun Li
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#[cfg(feature = "serde")]
+use serde::*;
+
+use super::geom::Cuboid;
+use super::placer::InnerSolution;
+
+/// The fitness minimized by the genetic algorithm, a weighted sum of the components below.
+/// Every component except `num_bins` is normalized into `[0, 1]`, so giving `num_bins` a
+/// weight of 1 keeps the number of bins as the primary goal.
+///
+/// The search stops as soon as the number of bins reaches the lower bound, so the other
+/// components only break ties between solutions found on the way there. They still guide the
+/// search: the default `least_load` tells apart solutions with the same number of bins, which
+/// finds fewer bins than counting bins alone.
+#[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Objective {
+    /// The number of bins used.
+    pub num_bins: f64,
+    /// The volume of the least loaded bin relative to the bin volume, minimizing it leaves
+    /// that bin as empty as possible so it can be reused.
+    pub least_load: f64,
+    /// The height of each bin's centre of gravity relative to the bin height, averaged over
+    /// the bins. Boxes are assumed to have a uniform density.
+    pub center_of_gravity: f64,
+    /// The fraction of boxes not packed in their original orientation.
+    pub orientation_changes: f64,
+}
+
+impl Default for Objective {
+    fn default() -> Self {
+        Objective {
+            num_bins: 1.,
+            least_load: 1.,
+            center_of_gravity: 0.,
+            orientation_changes: 0.,
+        }
+    }
+}
+
+impl Objective {
+    pub(crate) fn fitness(&self, solution: &InnerSolution, bin_spec: &Cuboid) -> f64 {
+        let bin_volume = bin_spec.volume() as f64;
+        let num_boxes = solution.num_boxes.max(1) as f64;
+
+        let center_of_gravity = solution
+            .bins
+            .iter()
+            .filter(|bin| bin.used_volume > 0)
+            .map(|bin| bin.height_moment / bin.used_volume as f64 / f64::from(bin_spec.height))
+            .sum::<f64>()
+            / solution.num_bins.max(1) as f64;
+
+        self.num_bins * solution.num_bins as f64
+            + self.least_load * solution.least_load as f64 / bin_volume
+            + self.center_of_gravity * center_of_gravity
+            + self.orientation_changes * solution.orientation_changes as f64 / num_boxes
+    }
+}
@@ -0,0 +1,214 @@
+/*
+ * Copyright 2019 Zejun Li
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Layer-based pallet loading.
+//!
+//! Cases are stacked in horizontal layers. Each layer is packed as a 2D problem: the cases
+//! keep their height and are only turned around the vertical axis, and the layer is as high
+//! as its tallest case. Layers are built from the tallest cases down and put on the first
+//! pallet with enough height left.
+
+use std::cmp::Reverse;
+use std::error::Error;
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::*;
+
+use super::geom::{Cuboid, Point, RotationType, Space};
+use super::{pack_boxes_with_report, Params, Placement};
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Pallet {
+    pub width: i32,
+    pub depth: i32,
+    /// The highest a stack may be, measured from the top of the pallet.
+    pub max_height: i32,
+    /// How far the cases may stick out over each edge of the pallet.
+    pub overhang: i32,
+    /// Cases at most this much lower than the tallest case of a layer may share it.
+    pub layer_tolerance: i32,
+}
+
+impl Pallet {
+    pub fn new(width: i32, depth: i32, max_height: i32) -> Self {
+        Pallet {
+            width,
+            depth,
+            max_height,
+            overhang: 0,
+            layer_tolerance: 0,
+        }
+    }
+
+    // the area the cases of a layer may cover.
+    fn footprint(&self, height: i32) -> Cuboid {
+        Cuboid::new(
+            self.width + 2 * self.overhang,
+            self.depth + 2 * self.overhang,
+            height,
+        )
+    }
+}
+
+/// A layer of cases, placed with the origin at the corner of the pallet, so an overhang gives
+/// negative coordinates.
+#[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Layer {
+    pub base: i32,
+    pub height: i32,
+    pub placements: Vec<Placement>,
+}
+
+#[derive(PartialEq, Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PalletLoad {
+    /// From the bottom up.
+    pub layers: Vec<Layer>,
+}
+
+impl PalletLoad {
+    pub fn height(&self) -> i32 {
+        self.layers.last().map_or(0, |l| l.base + l.height)
+    }
+}
+
+/// A case which does not fit on the pallet in either upright orientation.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct OversizeError {
+    pub item_idx: usize,
+    pub case: Cuboid,
+}
+
+impl fmt::Display for OversizeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let c = &self.case;
+        write!(
+            f,
+            "case {} ({}x{}x{}) does not fit on the pallet",
+            self.item_idx, c.width, c.depth, c.height
+        )
+    }
+}
+
+impl Error for OversizeError {}
+
+/// Stacks the cases on as few pallets as possible, `Placement::item_idx` being the index of
+/// the case. `params.box_rotation_type` is ignored, the cases are always kept upright.
+pub fn pack_pallets<'a, T>(
+    params: Params,
+    pallet: Pallet,
+    boxes: &'a [T],
+) -> Result<Vec<PalletLoad>, OversizeError>
+where
+    &'a T: Into<Cuboid>,
+{
+    let boxes: Vec<Cuboid> = boxes.iter().map(|b| b.into()).collect();
+    let footprint = pallet.footprint(pallet.max_height);
+    for (item_idx, b) in boxes.iter().enumerate() {
+        let upright = Cuboid::new(b.depth, b.width, b.height);
+        let fits = |c: &Cuboid| {
+            c.width <= footprint.width && c.depth <= footprint.depth && c.height <= footprint.height
+        };
+        if !fits(b) && !fits(&upright) {
+            return Err(OversizeError { item_idx, case: *b });
+        }
+    }
+
+    let params = Params {
+        box_rotation_type: RotationType::TwoDimension,
+        ..params
+    };
+    let mut remaining: Vec<usize> = (0..boxes.len()).collect();
+    remaining.sort_by_key(|&i| Reverse(boxes[i].height));
+
+    let mut loads: Vec<PalletLoad> = Vec::new();
+    while let Some(&tallest) = remaining.first() {
+        let lowest = boxes[tallest].height - pallet.layer_tolerance;
+        let candidates: Vec<usize> = remaining
+            .iter()
+            .cloned()
+            .take_while(|&i| boxes[i].height >= lowest)
+            .collect();
+        let mut layer = build_layer(params, &pallet, &boxes, &candidates);
+        remaining.retain(|&i| layer.placements.iter().all(|p| p.item_idx != i));
+
+        let load = match loads
+            .iter()
+            .position(|l| l.height() + layer.height <= pallet.max_height)
+        {
+            Some(idx) => &mut loads[idx],
+            None => {
+                loads.push(PalletLoad::default());
+                loads.last_mut().unwrap()
+            }
+        };
+        layer.base = load.height();
+        for p in &mut layer.placements {
+            p.space.bottom_left.y += layer.base;
+            p.space.upper_right.y += layer.base;
+        }
+        load.layers.push(layer);
+    }
+    Ok(loads)
+}
+
+// pack the candidates as rectangles as high as the layer, and keep the fullest bin.
+fn build_layer(params: Params, pallet: &Pallet, boxes: &[Cuboid], candidates: &[usize]) -> Layer {
+    let height = boxes[candidates[0]].height;
+    let rects: Vec<Cuboid> = candidates
+        .iter()
+        .map(|&i| Cuboid::new(boxes[i].width, boxes[i].depth, height))
+        .collect();
+    let report = pack_boxes_with_report(params, pallet.footprint(height), &rects);
+    let fullest = (0..report.bins.len())
+        .max_by(|&a, &b| {
+            report.utilization[a]
+                .partial_cmp(&report.utilization[b])
+                .unwrap()
+        })
+        .unwrap();
+
+    let placements: Vec<Placement> = report.bins[fullest]
+        .iter()
+        .map(|p| {
+            let item_idx = candidates[p.item_idx];
+            let s = &p.space;
+            let origin = Point::new(
+                s.bottom_left.x - pallet.overhang,
+                0,
+                s.bottom_left.z - pallet.overhang,
+            );
+            let size = Cuboid::new(s.width(), s.depth(), boxes[item_idx].height);
+            Placement {
+                space: Space::from_placement(&origin, &size),
+                item_idx,
+            }
+        })
+        .collect();
+    let height = placements
+        .iter()
+        .map(|p| p.space.height())
+        .max()
+        .unwrap_or(0);
+    Layer {
+        base: 0,
+        height,
+        placements,
+    }
+}
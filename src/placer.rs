@@ -15,37 +15,114 @@
  */
 
 use std::cell::RefCell;
-use std::i32;
+use std::cmp::Reverse;
 
+use super::ems::{EmsIndex, SideSpace, UNCHANGED};
 use super::ga::{Chromosome, Decoder as GADecoder};
 use super::geom::*;
+use super::objective::Objective;
+use super::Layout;
+
+// the orders in which the axes (x, y and z) of a block are filled, one per kind of block.
+const AXIS_ORDERS: [[usize; 3]; 6] = [
+    [0, 2, 1],
+    [2, 0, 1],
+    [0, 1, 2],
+    [1, 0, 2],
+    [2, 1, 0],
+    [1, 2, 0],
+];
 
 pub struct Decoder {
-    bin_volume: i32,
+    bin_spec: Cuboid,
+    objective: Objective,
+    lower_bound: usize,
     placer: Placer,
 }
 
 impl Decoder {
-    pub fn new<'a, T: 'a>(boxes: &'a [T], bin_spec: Cuboid, rotation_type: RotationType) -> Self
-    where
-        &'a T: Into<Cuboid>,
-    {
-        let boxes = boxes.iter().map(|b| b.into().into()).collect();
-        let bin_volume = bin_spec.volume();
-        let placer = Placer::new(boxes, bin_spec, rotation_type);
-        Decoder { placer, bin_volume }
+    pub fn new(
+        boxes: Vec<InnerBox>,
+        bin_spec: Cuboid,
+        rotation_type: RotationType,
+        objective: Objective,
+        layout: Layout,
+        guillotine: bool,
+        lower_bound: usize,
+    ) -> Self {
+        let placer = Placer::new(boxes, bin_spec, rotation_type, layout, guillotine);
+        Decoder {
+            placer,
+            bin_spec,
+            objective,
+            lower_bound,
+        }
+    }
+
+    // a fixed chromosome which packs boxes by volume then largest face, both in descending
+    // order, and always picks the first orientation (the original one if possible) that fits.
+    pub fn greedy_chromosome(&self) -> Chromosome {
+        let boxes = &self.placer.boxes;
+        let mut order: Vec<usize> = (0..boxes.len()).collect();
+        order.sort_by_key(|&i| {
+            let c = &boxes[i].cuboid;
+            let (w, d, h) = (i64::from(c.width), i64::from(c.depth), i64::from(c.height));
+            let largest_face = (w * d).max(w * h).max(d * h);
+            (-boxes[i].volume, -largest_face)
+        });
+
+        self.chromosome_of_order(&order)
+    }
+
+    // a chromosome packing the box types in the order they first appear in the solution, bin
+    // by bin, so the genetic algorithm starts near a solution found otherwise.
+    pub fn chromosome_of(&self, solution: &InnerSolution) -> Chromosome {
+        let mut placements: Vec<&InnerPlacement> = solution.placements.iter().collect();
+        placements.sort_by_key(|p| p.bin_no);
+
+        let mut seen = vec![false; self.placer.boxes.len()];
+        let mut order = Vec::with_capacity(seen.len());
+        for p in placements {
+            if !seen[p.box_idx] {
+                seen[p.box_idx] = true;
+                order.push(p.box_idx);
+            }
+        }
+        order.extend((0..seen.len()).filter(|&i| !seen[i]));
+        self.chromosome_of_order(&order)
+    }
+
+    fn chromosome_of_order(&self, order: &[usize]) -> Chromosome {
+        let num_boxes = self.placer.boxes.len();
+        let mut chromosome = vec![0.; num_boxes * 2];
+        for (rank, &box_idx) in order.iter().enumerate() {
+            chromosome[box_idx] = rank as f32 / num_boxes as f32;
+        }
+        chromosome
     }
 }
 
 impl GADecoder for Decoder {
     type Solution = InnerSolution;
 
-    fn decode_chromosome(&mut self, individual: &Chromosome) -> Self::Solution {
-        self.placer.place_boxes(individual)
+    fn decode_chromosome(&mut self, genes: &[f32], solution: &mut Self::Solution) {
+        let num_bins = self.placer.place_boxes(genes);
+        solution.summarize(num_bins, &self.placer.placements, &self.placer.boxes);
+        solution.placements.clear();
+    }
+
+    fn materialize(&mut self, genes: &[f32]) -> Self::Solution {
+        let num_bins = self.placer.place_boxes(genes);
+        let placements = self.placer.placements.clone();
+        InnerSolution::new(num_bins, placements, &self.placer.boxes)
     }
 
     fn fitness_of(&self, solution: &Self::Solution) -> f64 {
-        solution.num_bins as f64 + (f64::from(solution.least_load) / f64::from(self.bin_volume))
+        self.objective.fitness(solution, &self.bin_spec)
+    }
+
+    fn is_optimal(&self, solution: &Self::Solution) -> bool {
+        solution.num_bins <= self.lower_bound
     }
 
     fn reset(&mut self) {
@@ -56,72 +133,105 @@ impl GADecoder for Decoder {
 struct Placer {
     boxes: Vec<InnerBox>,
     rotation_type: RotationType,
+    layout: Layout,
 
     bins: BinList,
     bps: Vec<(usize, f32)>,
+    placements: Vec<InnerPlacement>,
     orientations: RefCell<Vec<Cuboid>>,
+    blocks: RefCell<Vec<(Cuboid, [usize; 3])>>,
 }
 
 impl Placer {
-    fn new(boxes: Vec<InnerBox>, bin_spec: Cuboid, rotation_type: RotationType) -> Self {
+    fn new(
+        boxes: Vec<InnerBox>,
+        bin_spec: Cuboid,
+        rotation_type: RotationType,
+        layout: Layout,
+        guillotine: bool,
+    ) -> Self {
         Placer {
             boxes,
             rotation_type,
-            bins: BinList::new(bin_spec),
+            layout,
+            bins: BinList::new(bin_spec, guillotine),
             bps: Vec::new(),
+            placements: Vec::new(),
             orientations: RefCell::new(Vec::new()),
+            blocks: RefCell::new(Vec::new()),
         }
     }
 
-    fn place_boxes(&mut self, chromosome: &Chromosome) -> InnerSolution {
-        let mut placements = Vec::with_capacity(self.boxes.len());
-        let (mut min_dimension, mut min_volume) = (i32::MAX, i32::MAX);
+    // place the boxes into `self.placements` and return the number of bins used. Identical
+    // boxes are placed together, as blocks filling as much of the chosen space as possible.
+    fn place_boxes(&mut self, chromosome: &[f32]) -> usize {
+        self.placements.clear();
+        let (mut min_dimension, mut min_volume) = (i32::MAX, i64::MAX);
 
         self.calculate_bps(chromosome);
         for (bps_idx, &(box_idx, _)) in self.bps.iter().enumerate() {
             let box_to_pack = &self.boxes[box_idx];
-            let (mut fit_bin, mut fit_space) = (None, None);
-
-            for (i, bin) in self.bins.opened().iter().enumerate() {
-                let placement = bin.try_place_cuboid(&box_to_pack.cuboid, self.rotation_type);
-                if let Some(space) = placement {
-                    fit_space = Some(space);
-                    fit_bin = Some(i);
-                    break;
-                }
-            }
-
-            if fit_bin.is_none() {
-                let idx = self.bins.open_new_bin();
-                fit_bin = Some(idx);
-                fit_space = Some(&self.bins.nth(idx).empty_space_list[0]);
-            }
-
-            let (fit_bin, fit_space) = (fit_bin.unwrap(), fit_space.unwrap());
-            let placement = self.place_box(box_idx, chromosome, fit_space);
-
             if box_to_pack.smallest_dimension <= min_dimension || box_to_pack.volume <= min_volume {
                 let (md, mv) = self.min_dimension_and_volume(&self.bps[bps_idx + 1..]);
                 min_dimension = md;
                 min_volume = mv;
             }
 
-            self.bins.nth_mut(fit_bin).allocate_space(&placement, |ns| {
-                let (w, d, h) = (ns.width(), ns.depth(), ns.height());
-                let v = w * d * h;
-                w.min(d).min(h) >= min_dimension && v >= min_volume
-            });
+            let mut remaining = box_to_pack.quantity;
+            while remaining > 0 {
+                let (mut fit_bin, mut fit_space) = (None, None);
+                for (i, bin) in self.bins.opened().iter().enumerate() {
+                    let placement = bin.try_place_cuboid(&box_to_pack.cuboid, self.rotation_type);
+                    if let Some(space) = placement {
+                        fit_space = Some(space);
+                        fit_bin = Some(i);
+                        break;
+                    }
+                }
+
+                if fit_bin.is_none() {
+                    let idx = self.bins.open_new_bin();
+                    fit_bin = Some(idx);
+                    fit_space = Some(self.bins.nth(idx).empty_spaces.nth(0));
+                }
 
-            placements.push(InnerPlacement::new(placement, fit_bin, box_idx));
+                let (fit_bin, fit_space) = (fit_bin.unwrap(), fit_space.unwrap());
+                let (placement, counts) = match self.layout {
+                    Layout::Fill => {
+                        let placement = self.place_box(box_idx, chromosome, fit_space);
+                        let size = size_of(&placement);
+                        (
+                            placement,
+                            block_counts(fit_space, &size, remaining, AXIS_ORDERS[0]),
+                        )
+                    }
+                    Layout::Blocks => self.place_block(box_idx, chromosome, fit_space, remaining),
+                };
+                remaining -= counts.iter().product::<usize>();
+
+                // the boxes of this type left behind need space too.
+                let (md, mv) = if remaining > 0 {
+                    (
+                        min_dimension.min(box_to_pack.smallest_dimension),
+                        min_volume.min(box_to_pack.volume),
+                    )
+                } else {
+                    (min_dimension, min_volume)
+                };
+                let block = block_space(&placement, &counts);
+                self.bins.nth_mut(fit_bin).allocate_space(&block, |ns| {
+                    let (w, d, h) = (ns.width(), ns.depth(), ns.height());
+                    w.min(d).min(h) >= md && ns.volume() >= mv
+                });
+
+                push_block(&mut self.placements, &placement, &counts, fit_bin, box_idx);
+            }
         }
 
-        let bins = self.bins.opened();
-        let num_bins = bins.len();
-        let least_load = bins.iter().map(|bin| bin.used_volume).min().unwrap();
-        InnerSolution::new(num_bins, least_load, placements)
+        self.bins.opened().len()
     }
 
-    fn place_box(&self, box_idx: usize, chromosome: &Chromosome, container: &Space) -> Space {
+    fn place_box(&self, box_idx: usize, chromosome: &[f32], container: &Space) -> Space {
         let cuboid = &self.boxes[box_idx].cuboid;
         let gene = chromosome[chromosome.len() / 2 + box_idx];
 
@@ -135,14 +245,55 @@ impl Placer {
         Space::from_placement(container.origin(), orientation)
     }
 
+    // pick a block of at most `quantity` boxes for `container` with the orientation gene,
+    // the larger blocks first.
+    fn place_block(
+        &self,
+        box_idx: usize,
+        chromosome: &[f32],
+        container: &Space,
+        quantity: usize,
+    ) -> (Space, [usize; 3]) {
+        let cuboid = &self.boxes[box_idx].cuboid;
+        let gene = chromosome[chromosome.len() / 2 + box_idx];
+
+        let mut orientations = self.orientations.borrow_mut();
+        orientations.clear();
+        rotate_cuboid(self.rotation_type, cuboid, orientations.as_mut());
+        orientations.retain(|c| c.can_fit_in(container));
+
+        let mut blocks = self.blocks.borrow_mut();
+        blocks.clear();
+        for orientation in orientations.iter() {
+            for &order in AXIS_ORDERS.iter() {
+                let block = (
+                    *orientation,
+                    block_counts(container, orientation, quantity, order),
+                );
+                if !blocks.contains(&block) {
+                    blocks.push(block);
+                }
+            }
+        }
+        blocks.sort_by_key(|(_, counts)| Reverse(counts.iter().product::<usize>()));
+
+        let decoded_gene = (gene * blocks.len() as f32).ceil() as usize;
+        let (orientation, counts) = blocks[decoded_gene.max(1) - 1];
+        (
+            Space::from_placement(container.origin(), &orientation),
+            counts,
+        )
+    }
+
     fn reset(&mut self) {
         self.bins.reset();
         self.bps.clear();
         self.orientations.borrow_mut().clear();
+        self.blocks.borrow_mut().clear();
     }
 
-    fn min_dimension_and_volume(&self, remain_bps: &[(usize, f32)]) -> (i32, i32) {
-        let (mut min_d, mut min_v) = (i32::MAX, i32::MAX);
+    fn min_dimension_and_volume(&self, remain_bps: &[(usize, f32)]) -> (i32, i64) {
+        let (mut min_d, mut min_v) = (i32::MAX, i64::MAX);
         for &(box_idx, _) in remain_bps {
             let b = &self.boxes[box_idx];
             min_d = min_d.min(b.smallest_dimension);
@@ -152,7 +303,7 @@ impl Placer {
     }
 
     #[inline]
-    fn calculate_bps(&mut self, chromosome: &Chromosome) {
+    fn calculate_bps(&mut self, chromosome: &[f32]) {
         self.bps.clear();
         let bps = chromosome[..chromosome.len() / 2]
             .iter()
@@ -167,14 +318,16 @@ impl Placer {
 
 struct BinList {
     spec: Cuboid,
+    guillotine: bool,
     bins: Vec<InnerBin>,
     size: usize,
 }
 
 impl BinList {
-    fn new(spec: Cuboid) -> Self {
+    fn new(spec: Cuboid, guillotine: bool) -> Self {
         BinList {
             spec,
+            guillotine,
             bins: Vec::new(),
             size: 0,
         }
@@ -195,7 +348,7 @@ impl BinList {
     fn open_new_bin(&mut self) -> usize {
         let buffered = self.bins.len() - self.size;
         if buffered == 0 {
-            self.bins.push(InnerBin::new(self.spec));
+            self.bins.push(InnerBin::new(self.spec, self.guillotine));
         } else {
             self.bins[self.size].reset();
         }
@@ -208,22 +361,26 @@ impl BinList {
     }
 }
 
-struct InnerBin {
+#[derive(Clone)]
+pub struct InnerBin {
     spec: Cuboid,
-    used_volume: i32,
+    // split the empty spaces with edge-to-edge cuts, leaving them disjoint.
+    guillotine: bool,
+    pub used_volume: i64,
 
-    empty_space_list: Vec<Space>,
+    pub empty_spaces: EmsIndex,
     spaces_intersects: Vec<usize>,
-    new_empty_spaces: Vec<Space>,
+    new_empty_spaces: Vec<SideSpace>,
     orientations: RefCell<Vec<Cuboid>>,
 }
 
 impl InnerBin {
-    fn new(spec: Cuboid) -> Self {
-        let empty_space_list = vec![Space::from_placement(&Point::new(0, 0, 0), &spec)];
+    pub fn new(spec: Cuboid, guillotine: bool) -> Self {
+        let empty_spaces = EmsIndex::new(Space::from_placement(&Point::new(0, 0, 0), &spec));
         InnerBin {
             spec,
-            empty_space_list,
+            guillotine,
+            empty_spaces,
             used_volume: 0,
             spaces_intersects: Vec::new(),
             new_empty_spaces: Vec::new(),
@@ -238,17 +395,20 @@ impl InnerBin {
 
         orientations.clear();
         rotate_cuboid(rotation_type, cuboid, orientations.as_mut());
+        if !self.empty_spaces.may_hold(&orientations) {
+            return None;
+        }
         let container_upper_right = Point::new(self.spec.width, self.spec.depth, self.spec.height);
 
-        for ems in &self.empty_space_list {
-            if ems.volume() >= cuboid.volume() {
-                for o in orientations.iter().filter(|o| o.can_fit_in(ems)) {
-                    let box_upper_right = Space::from_placement(ems.origin(), o).upper_right;
-                    let dist = container_upper_right.distance2_from(&box_upper_right);
-                    if dist > max_dist {
-                        max_dist = dist;
-                        best_ems = Some(ems);
-                    }
+        for ems in self.empty_spaces.at_least(cuboid.volume()) {
+            for o in orientations.iter().filter(|o| o.can_fit_in(ems)) {
+                let box_upper_right = Space::from_placement(ems.origin(), o).upper_right;
+                let dist = container_upper_right.distance2_from(&box_upper_right);
+                // break ties by the x of the origin, the spaces being in order of volume.
+                let closer = best_ems.is_none_or(|b: &Space| ems.bottom_left.x < b.bottom_left.x);
+                if dist > max_dist || dist == max_dist && closer {
+                    max_dist = dist;
+                    best_ems = Some(ems);
                 }
             }
         }
@@ -256,45 +416,47 @@ impl InnerBin {
         best_ems
     }
 
-    fn allocate_space<F>(&mut self, space: &Space, mut new_space_filter: F)
+    pub fn allocate_space<F>(&mut self, space: &Space, mut new_space_filter: F)
     where
         F: FnMut(&Space) -> bool,
     {
         self.used_volume += space.volume();
 
         self.spaces_intersects.clear();
-        let spaces_intersects = self
-            .empty_space_list
-            .iter()
-            .enumerate()
-            .filter(|(_, ems)| ems.intersects(space))
-            .map(|(i, _)| i);
-        self.spaces_intersects.extend(spaces_intersects);
+        self.empty_spaces
+            .intersecting(space, &mut self.spaces_intersects);
 
         self.new_empty_spaces.clear();
-        for &i in self.spaces_intersects.iter() {
-            let ems = &self.empty_space_list[i];
-            let union = ems.union(space);
-            difference_process(ems, &union, &mut self.new_empty_spaces, |s| {
-                new_space_filter(s)
-            })
-        }
-
-        for &i in self.spaces_intersects.iter().rev() {
-            self.empty_space_list.swap_remove(i);
-        }
-        self.empty_space_list.retain(|s| new_space_filter(s));
-
-        for (i, this) in self.new_empty_spaces.iter().enumerate() {
-            let overlapped = self
-                .new_empty_spaces
-                .iter()
-                .enumerate()
-                .any(|(j, other)| i != j && other.contains(this));
-            if !overlapped {
-                self.empty_space_list.push(*this);
+        let mut overlapping = 0;
+        for k in 0..self.spaces_intersects.len() {
+            let idx = self.spaces_intersects[k];
+            let ems = self.empty_spaces.nth(idx);
+            // a space only touching the allocated one stays as it is, but may still contain
+            // the new ones.
+            if ems.union(space).volume() == 0 {
+                self.new_empty_spaces.push((UNCHANGED, *ems));
+                continue;
+            }
+            self.spaces_intersects[overlapping] = idx;
+            overlapping += 1;
+            if self.guillotine {
+                guillotine_process(ems, space, &mut self.new_empty_spaces, |s| {
+                    new_space_filter(s)
+                })
+            } else {
+                let union = ems.union(space);
+                difference_process(ems, &union, &mut self.new_empty_spaces, |s| {
+                    new_space_filter(s)
+                })
             }
         }
+
+        self.spaces_intersects.truncate(overlapping);
+        self.empty_spaces.update(
+            &self.spaces_intersects,
+            &mut self.new_empty_spaces,
+            new_space_filter,
+        );
     }
 
     #[inline]
@@ -303,9 +465,67 @@ impl InnerBin {
         self.orientations.borrow_mut().clear();
         self.new_empty_spaces.clear();
         self.spaces_intersects.clear();
-        self.empty_space_list.clear();
-        self.empty_space_list
-            .push(Space::from_placement(&Point::new(0, 0, 0), &self.spec))
+        self.empty_spaces
+            .reset(Space::from_placement(&Point::new(0, 0, 0), &self.spec))
+    }
+}
+
+// the number of copies of a box of `size` along x, y and z (width, height and depth) in the
+// largest block of at most `quantity` boxes which fits in `container`, filling the axes in
+// `order`.
+fn block_counts(
+    container: &Space,
+    size: &Cuboid,
+    quantity: usize,
+    order: [usize; 3],
+) -> [usize; 3] {
+    let room = [
+        container.width() / size.width,
+        container.height() / size.height,
+        container.depth() / size.depth,
+    ];
+    let (mut counts, mut left) = ([1; 3], quantity);
+    for &axis in order.iter() {
+        counts[axis] = (room[axis] as usize).min(left).max(1);
+        left /= counts[axis];
+    }
+    counts
+}
+
+fn size_of(space: &Space) -> Cuboid {
+    Cuboid::new(space.width(), space.depth(), space.height())
+}
+
+fn block_space(placement: &Space, counts: &[usize; 3]) -> Space {
+    let size = Cuboid::new(
+        placement.width() * counts[0] as i32,
+        placement.depth() * counts[2] as i32,
+        placement.height() * counts[1] as i32,
+    );
+    Space::from_placement(placement.origin(), &size)
+}
+
+fn push_block(
+    placements: &mut Vec<InnerPlacement>,
+    placement: &Space,
+    counts: &[usize; 3],
+    bin_no: usize,
+    box_idx: usize,
+) {
+    let size = size_of(placement);
+    let origin = placement.origin();
+    for y in 0..counts[1] as i32 {
+        for z in 0..counts[2] as i32 {
+            for x in 0..counts[0] as i32 {
+                let corner = Point::new(
+                    origin.x + x * size.width,
+                    origin.y + y * size.height,
+                    origin.z + z * size.depth,
+                );
+                let space = Space::from_placement(&corner, &size);
+                placements.push(InnerPlacement::new(space, bin_no, box_idx));
+            }
+        }
     }
 }
 
@@ -313,7 +533,7 @@ impl InnerBin {
 fn difference_process<F>(
     this: &Space,
     other: &Space,
-    new_spaces: &mut Vec<Space>,
+    new_spaces: &mut Vec<SideSpace>,
     mut new_space_filter: F,
 ) where
     F: FnMut(&Space) -> bool,
@@ -335,13 +555,47 @@ fn difference_process<F>(
 
     let spaces = spaces
         .iter()
-        .filter(|ns| ns.width().min(ns.depth()).min(ns.height()) != 0 && new_space_filter(ns));
-    for space in spaces {
-        new_spaces.push(*space);
+        .enumerate()
+        .filter(|(_, ns)| ns.width().min(ns.depth()).min(ns.height()) != 0 && new_space_filter(ns));
+    for (side, space) in spaces {
+        new_spaces.push((side, *space));
+    }
+}
+
+// cut `this` around `other`, which lies at its origin: across the height above `other`, then
+// across the width and the depth beside it. The pieces are disjoint, so every empty space
+// stays the result of edge-to-edge cuts through the bin.
+#[inline]
+fn guillotine_process<F>(
+    this: &Space,
+    other: &Space,
+    new_spaces: &mut Vec<SideSpace>,
+    mut new_space_filter: F,
+) where
+    F: FnMut(&Space) -> bool,
+{
+    let (sb, su, ou) = (&this.bottom_left, &this.upper_right, &other.upper_right);
+    let spaces = [
+        (3, Space::new(Point::new(sb.x, ou.y, sb.z), *su)),
+        (
+            1,
+            Space::new(Point::new(ou.x, sb.y, sb.z), Point::new(su.x, ou.y, su.z)),
+        ),
+        (
+            5,
+            Space::new(Point::new(sb.x, sb.y, ou.z), Point::new(ou.x, ou.y, su.z)),
+        ),
+    ];
+
+    let spaces = spaces
+        .iter()
+        .filter(|(_, ns)| ns.width().min(ns.depth()).min(ns.height()) != 0 && new_space_filter(ns));
+    for &(side, space) in spaces {
+        new_spaces.push((side, space));
     }
 }
 
-fn rotate_cuboid(tp: RotationType, cuboid: &Cuboid, orientations: &mut Vec<Cuboid>) {
+pub fn rotate_cuboid(tp: RotationType, cuboid: &Cuboid, orientations: &mut Vec<Cuboid>) {
     let only_2d = match tp {
         RotationType::TwoDimension => true,
         RotationType::ThreeDimension => false,
@@ -377,7 +631,7 @@ pub struct InnerPlacement {
 }
 
 impl InnerPlacement {
-    fn new(space: Space, bin_no: usize, box_idx: usize) -> Self {
+    pub fn new(space: Space, bin_no: usize, box_idx: usize) -> Self {
         InnerPlacement {
             space,
             bin_no,
@@ -386,11 +640,24 @@ impl InnerPlacement {
     }
 }
 
-#[derive(Debug)]
+// `quantity` identical boxes.
+#[derive(Clone, Debug)]
 pub struct InnerBox {
     pub cuboid: Cuboid,
     pub smallest_dimension: i32,
-    pub volume: i32,
+    pub volume: i64,
+    pub quantity: usize,
+}
+
+impl InnerBox {
+    pub fn new(cuboid: Cuboid, quantity: usize) -> Self {
+        InnerBox {
+            cuboid,
+            smallest_dimension: cuboid.height.min(cuboid.width).min(cuboid.depth),
+            volume: cuboid.volume(),
+            quantity,
+        }
+    }
 }
 
 impl<T> From<T> for InnerBox
@@ -398,30 +665,80 @@ where
     T: Into<Cuboid>,
 {
     fn from(raw: T) -> Self {
-        let rect = raw.into();
-        let smallest_dimension = rect.height.min(rect.width).min(rect.depth);
-        let volume = rect.volume();
-        InnerBox {
-            cuboid: rect,
-            smallest_dimension,
-            volume,
-        }
+        InnerBox::new(raw.into(), 1)
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
+pub struct BinSummary {
+    pub used_volume: i64,
+    // the sum of each box's volume times the height of its centre.
+    pub height_moment: f64,
+}
+
+#[derive(Debug, Default)]
 pub struct InnerSolution {
     pub num_bins: usize,
-    pub least_load: i32,
+    pub num_boxes: usize,
+    pub least_load: i64,
+    pub orientation_changes: usize,
+    pub bins: Vec<BinSummary>,
     pub placements: Vec<InnerPlacement>,
 }
 
 impl InnerSolution {
-    fn new(num_bins: usize, least_load: i32, placements: Vec<InnerPlacement>) -> Self {
+    pub fn new(num_bins: usize, placements: Vec<InnerPlacement>, boxes: &[InnerBox]) -> Self {
+        let mut solution = InnerSolution::default();
+        solution.summarize(num_bins, &placements, boxes);
+        solution.placements = placements;
+        solution
+    }
+
+    // compute the summaries of `placements`, which are spread over `num_bins` bins. The
+    // placements themselves are left out.
+    fn summarize(&mut self, num_bins: usize, placements: &[InnerPlacement], boxes: &[InnerBox]) {
+        let bins = &mut self.bins;
+        bins.clear();
+        bins.resize(num_bins, BinSummary::default());
+        let mut orientation_changes = 0;
+        for p in placements {
+            let (bin, space) = (&mut bins[p.bin_no], &p.space);
+            let volume = space.volume();
+            bin.used_volume += volume;
+            bin.height_moment += volume as f64 * space.center().1;
+
+            let c = &boxes[p.box_idx].cuboid;
+            if (space.width(), space.depth(), space.height()) != (c.width, c.depth, c.height) {
+                orientation_changes += 1;
+            }
+        }
+
+        self.least_load = bins.iter().map(|bin| bin.used_volume).min().unwrap_or(0);
+        self.num_bins = num_bins;
+        self.num_boxes = placements.len();
+        self.orientation_changes = orientation_changes;
+    }
+}
+
+// `clone_from` reuses the buffers of the target, elites are copied this way every generation.
+impl Clone for InnerSolution {
+    fn clone(&self) -> Self {
         InnerSolution {
-            num_bins,
-            least_load,
-            placements,
+            num_bins: self.num_bins,
+            num_boxes: self.num_boxes,
+            least_load: self.least_load,
+            orientation_changes: self.orientation_changes,
+            bins: self.bins.clone(),
+            placements: self.placements.clone(),
         }
     }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.num_bins = source.num_bins;
+        self.num_boxes = source.num_boxes;
+        self.least_load = source.least_load;
+        self.orientation_changes = source.orientation_changes;
+        self.bins.clone_from(&source.bins);
+        self.placements.clone_from(&source.placements);
+    }
 }
@@ -0,0 +1,214 @@
+/*
+ * Copyright 2019 Zejun Li
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Fractional dimensions in the caller's units.
+//!
+//! The packer works on an integer grid. `Units` maps `f64` dimensions onto it conservatively:
+//! boxes are rounded up and bins are rounded down, so every packing found on the grid is
+//! also valid with the exact dimensions.
+
+use std::error::Error;
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::*;
+
+use super::geom::{Cuboid, Point, Space};
+use super::FitError;
+
+// values closer than this (relative) to a grid line are taken as lying on it, so
+// `12.3 / 0.1` is 123 steps rather than 124.
+const TOLERANCE: f64 = 1e-9;
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Dimensions {
+    pub width: f64,
+    pub depth: f64,
+    pub height: f64,
+}
+
+impl Dimensions {
+    pub fn new(width: f64, depth: f64, height: f64) -> Self {
+        Dimensions {
+            width,
+            depth,
+            height,
+        }
+    }
+}
+
+impl From<&Dimensions> for Dimensions {
+    fn from(dimensions: &Dimensions) -> Self {
+        *dimensions
+    }
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Position {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Region {
+    pub bottom_left: Position,
+    pub upper_right: Position,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct UnitPlacement {
+    pub region: Region,
+    pub item_idx: usize,
+}
+
+/// A dimension which can not be put on the grid: negative, not finite or too large.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct ConversionError {
+    /// The offending box when packing, `None` for the bin or a single conversion.
+    pub item_idx: Option<usize>,
+    pub value: f64,
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.item_idx {
+            Some(idx) => write!(f, "invalid dimension {} of box {}", self.value, idx),
+            None => write!(f, "invalid dimension {}", self.value),
+        }
+    }
+}
+
+impl Error for ConversionError {}
+
+/// A problem in the caller's units which can not be packed, either as a dimension has no grid
+/// value or as the boxes on the grid do not fit in the bin.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum UnitsError {
+    Conversion(ConversionError),
+    Fit(FitError),
+}
+
+impl fmt::Display for UnitsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UnitsError::Conversion(err) => write!(f, "{}", err),
+            UnitsError::Fit(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for UnitsError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            UnitsError::Conversion(err) => Some(err),
+            UnitsError::Fit(err) => Some(err),
+        }
+    }
+}
+
+impl From<ConversionError> for UnitsError {
+    fn from(err: ConversionError) -> Self {
+        UnitsError::Conversion(err)
+    }
+}
+
+impl From<FitError> for UnitsError {
+    fn from(err: FitError) -> Self {
+        UnitsError::Fit(err)
+    }
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Units {
+    /// The size of one grid step in the caller's units, e.g. `0.1` for centimetres with one
+    /// decimal place.
+    pub precision: f64,
+}
+
+impl Units {
+    pub fn new(precision: f64) -> Self {
+        assert!(
+            precision.is_finite() && precision > 0.,
+            "precision must be positive"
+        );
+        Units { precision }
+    }
+
+    /// The smallest grid cuboid the box fits in.
+    pub fn box_to_grid(&self, dimensions: &Dimensions) -> Result<Cuboid, ConversionError> {
+        self.round_to_grid(dimensions, f64::ceil)
+    }
+
+    /// The largest grid cuboid which fits in the bin.
+    pub fn bin_to_grid(&self, dimensions: &Dimensions) -> Result<Cuboid, ConversionError> {
+        self.round_to_grid(dimensions, f64::floor)
+    }
+
+    pub fn point_from_grid(&self, point: &Point) -> Position {
+        Position {
+            x: f64::from(point.x) * self.precision,
+            y: f64::from(point.y) * self.precision,
+            z: f64::from(point.z) * self.precision,
+        }
+    }
+
+    pub fn space_from_grid(&self, space: &Space) -> Region {
+        Region {
+            bottom_left: self.point_from_grid(&space.bottom_left),
+            upper_right: self.point_from_grid(&space.upper_right),
+        }
+    }
+
+    fn round_to_grid<R>(&self, dimensions: &Dimensions, round: R) -> Result<Cuboid, ConversionError>
+    where
+        R: Fn(f64) -> f64,
+    {
+        let steps = |value: f64| {
+            let steps = value / self.precision;
+            let nearest = steps.round();
+            let steps = if (steps - nearest).abs() <= TOLERANCE * nearest.abs().max(1.) {
+                nearest
+            } else {
+                round(steps)
+            };
+            if steps.is_finite() && steps >= 0. && steps <= f64::from(i32::MAX) {
+                Ok(steps as i32)
+            } else {
+                Err(ConversionError {
+                    item_idx: None,
+                    value,
+                })
+            }
+        };
+        Ok(Cuboid::new(
+            steps(dimensions.width)?,
+            steps(dimensions.depth)?,
+            steps(dimensions.height)?,
+        ))
+    }
+}
+
+impl Default for Units {
+    fn default() -> Self {
+        Units { precision: 1. }
+    }
+}
@@ -0,0 +1,73 @@
+/*
+ * Copyright 2019 Zejun Li
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::time::Duration;
+
+use kaosu_packer::geom::Cuboid;
+use kaosu_packer::*;
+
+#[test]
+fn a_job_which_does_not_fit_fails_alone() {
+    let params = Params {
+        max_generations: 5,
+        seed: Some(1),
+        ..Params::default()
+    };
+    let jobs = [
+        Job {
+            params,
+            bin_spec: Cuboid::new(10, 10, 10),
+            boxes: vec![Cuboid::new(20, 5, 5)],
+        },
+        Job {
+            params,
+            bin_spec: Cuboid::new(10, 10, 10),
+            boxes: vec![Cuboid::new(5, 5, 5); 4],
+        },
+    ];
+
+    let reports = pack_batch(&jobs, Duration::from_secs(10));
+    let cuboid = Cuboid::new(20, 5, 5);
+    assert_eq!(
+        reports[0].as_ref().err(),
+        Some(&FitError::Oversize {
+            item_idx: 0,
+            cuboid
+        })
+    );
+    assert_eq!(reports[1].as_ref().map(|r| r.bins.len()), Ok(1));
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn a_shared_pool_packs_like_the_threads_param() {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(2)
+        .build()
+        .unwrap();
+    let params = Params {
+        max_generations: 5,
+        seed: Some(1),
+        threads: 2,
+        ..Params::default()
+    };
+    let bin = Cuboid::new(10, 10, 10);
+    let boxes = [Cuboid::new(5, 5, 3); 12];
+
+    let shared = pack_boxes_in(&pool, params, bin, &boxes);
+    let own = pack_boxes_with_report(params, bin, &boxes);
+    assert_eq!(shared.bins, own.bins);
+}
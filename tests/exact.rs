@@ -0,0 +1,47 @@
+/*
+ * Copyright 2019 Zejun Li
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use kaosu_packer::geom::{Cuboid, RotationType};
+use kaosu_packer::objective::Objective;
+use kaosu_packer::*;
+
+// four boxes around a unit cube fill the bin, which the exact solver can not reach.
+#[test]
+fn pinwheel_is_not_proven_by_the_exact_solver() {
+    let bin = Cuboid::new(3, 1, 3);
+    let boxes = [
+        Cuboid::new(2, 1, 1),
+        Cuboid::new(2, 1, 1),
+        Cuboid::new(1, 1, 2),
+        Cuboid::new(1, 1, 2),
+        Cuboid::new(1, 1, 1),
+    ];
+    let params = Params {
+        box_rotation_type: RotationType::TwoDimension,
+        objective: Objective {
+            least_load: 0.,
+            ..Objective::default()
+        },
+        exact_threshold: 12,
+        seed: Some(1),
+        ..Params::default()
+    };
+
+    assert_eq!(pack_boxes_exact(params, bin, &boxes).len(), 2);
+    let report = pack_boxes_with_report(params, bin, &boxes);
+    assert_eq!(report.bins.len(), 1);
+    assert!(report.optimal);
+}
@@ -0,0 +1,56 @@
+/*
+ * Copyright 2019 Zejun Li
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use kaosu_packer::geom::Cuboid;
+use kaosu_packer::*;
+
+// three individuals per type leave no room for an elite at the default percentage.
+#[test]
+fn small_population_keeps_an_elite() {
+    let bin = Cuboid::new(10, 10, 10);
+    let types = [
+        BoxType::new(Cuboid::new(5, 5, 5), 4),
+        BoxType::new(Cuboid::new(3, 3, 3), 6),
+    ];
+    let params = Params {
+        population_factor: 3,
+        max_generations: 5,
+        seed: Some(1),
+        ..Params::default()
+    };
+
+    let solution = pack_box_types(params, bin, &types);
+    assert_eq!(solution.iter().map(Vec::len).sum::<usize>(), 10);
+}
+
+#[test]
+fn negative_clearance_is_rejected() {
+    let bin = Cuboid::new(10, 10, 10);
+    let boxes = [Cuboid::new(5, 5, 5); 8];
+    let spacing = Spacing {
+        clearance: -3,
+        margin: 0,
+    };
+    let params = Params {
+        spacing,
+        ..Params::default()
+    };
+
+    assert_eq!(
+        params.check_fit(bin, &boxes),
+        Err(FitError::NegativeSpacing(spacing))
+    );
+}
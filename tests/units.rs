@@ -0,0 +1,30 @@
+/*
+ * Copyright 2019 Zejun Li
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use kaosu_packer::units::{Dimensions, Units, UnitsError};
+use kaosu_packer::*;
+
+#[test]
+fn a_box_too_large_after_rounding_is_an_error() {
+    let bin = Dimensions::new(10., 10., 10.);
+    let boxes = [Dimensions::new(20., 1., 1.)];
+
+    let result = pack_boxes_in_units(Params::default(), Units::new(0.5), bin, &boxes);
+    match result {
+        Err(UnitsError::Fit(FitError::Oversize { item_idx, .. })) => assert_eq!(item_idx, 0),
+        _ => panic!("expected an oversize error"),
+    }
+}
@@ -15,7 +15,6 @@
  */
 
 use std::cell::RefCell;
-use std::error::Error;
 use std::iter;
 use std::rc::Rc;
 use std::str::FromStr;
@@ -76,7 +75,7 @@ impl Component for InputProcess {
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
         match self.inner_update(msg) {
             Err(err) => {
-                stdweb::web::alert(err.description());
+                stdweb::web::alert(&err.to_string());
                 false
             }
             Ok(ret) => ret,
@@ -193,7 +192,7 @@ fn parse_csv(content: &str, name: String) -> Result<(Vec<Item>, String)> {
 
     for (id, spec) in rdr.deserialize().enumerate() {
         let spec: BoxGroup = spec?;
-        items.extend(iter::repeat(spec.into_item(id)).take(spec.count));
+        items.extend(iter::repeat_n(spec.into_item(id), spec.count));
     }
 
     Ok((items, name))
@@ -386,9 +385,9 @@ impl InputProcess {
     }
 
     fn is_rotate_3d(&self) -> bool {
-        match self.problem_spec.borrow().params.box_rotation_type {
-            RotationType::ThreeDimension => true,
-            _ => false,
-        }
+        matches!(
+            self.problem_spec.borrow().params.box_rotation_type,
+            RotationType::ThreeDimension
+        )
     }
 }
@@ -38,11 +38,11 @@ use self::input_process::InputProcess;
 pub use self::packer::Packer;
 use self::types::*;
 use self::visualize::Visualize;
-use kaosu_packer::PackSolution;
+use kaosu_packer::PackReport;
 
 pub enum Msg {
     Submit(Rc<RefCell<ProblemSpec>>),
-    PackResult(PackSolution),
+    PackResult(PackReport),
 }
 
 enum Page {
@@ -52,9 +52,9 @@ enum Page {
 }
 
 pub struct App {
-    pack_worker: Box<Bridge<packer::Packer>>,
+    pack_worker: Box<dyn Bridge<packer::Packer>>,
     problem_spec: Option<Rc<RefCell<ProblemSpec>>>,
-    pack_solution: Option<Rc<RefCell<PackSolution>>>,
+    pack_report: Option<Rc<RefCell<PackReport>>>,
     current_page: Page,
 }
 
@@ -64,12 +64,12 @@ impl Component for App {
 
     fn create(_: Self::Properties, mut link: ComponentLink<Self>) -> Self {
         let callback = link.send_back(|resp: packer::Response| match resp {
-            packer::Response::Solution(solution) => Msg::PackResult(solution),
+            packer::Response::Solution(report) => Msg::PackResult(report),
         });
         let pack_worker = packer::Packer::bridge(callback);
         App {
             pack_worker,
-            pack_solution: None,
+            pack_report: None,
             problem_spec: None,
             current_page: Page::InputProcess,
         }
@@ -84,8 +84,8 @@ impl Component for App {
                 self.current_page = Page::Computing;
                 true
             }
-            Msg::PackResult(solution) => {
-                self.pack_solution = Some(Rc::new(RefCell::new(solution)));
+            Msg::PackResult(report) => {
+                self.pack_report = Some(Rc::new(RefCell::new(report)));
                 self.current_page = Page::Visualize;
                 true
             }
@@ -106,7 +106,7 @@ impl Renderable<App> for App {
                 </div>
             },
             Page::Visualize => html! {
-                <Visualize: solution=self.pack_solution.as_ref().unwrap().clone(),
+                <Visualize: report=self.pack_report.as_ref().unwrap().clone(),
                             problem_spec=self.problem_spec.as_ref().unwrap().clone(),/>
             },
         }
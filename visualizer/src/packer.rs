@@ -18,7 +18,7 @@ use serde::*;
 use yew::prelude::worker::*;
 
 use super::types::*;
-use kaosu_packer::{pack_boxes, PackSolution};
+use kaosu_packer::{pack_boxes_with_report, PackReport};
 
 pub struct Packer {
     link: AgentLink<Packer>,
@@ -33,7 +33,7 @@ impl Transferable for Request {}
 
 #[derive(Serialize, Deserialize, Debug)]
 pub enum Response {
-    Solution(PackSolution),
+    Solution(PackReport),
 }
 
 impl Transferable for Response {}
@@ -53,7 +53,7 @@ impl Agent for Packer {
     fn handle(&mut self, msg: Self::Input, who: HandlerId) {
         match msg {
             Request::Problem(input) => {
-                let result = pack_boxes(input.params, input.bin, &input.items);
+                let result = pack_boxes_with_report(input.params, input.bin, &input.items);
                 self.link.response(who, Response::Solution(result));
             }
         }
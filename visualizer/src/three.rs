@@ -24,6 +24,7 @@ use kaosu_packer::geom::{Cuboid, Space};
 
 #[derive(Clone)]
 pub struct ThreeRender {
+    #[allow(dead_code)]
     canvas: web::Element,
     scene: Value,
     render: Value,
@@ -83,6 +84,8 @@ impl ThreeRender {
         three_render
     }
 
+    // the js! macro expands to a function taking every interpolated value.
+    #[allow(clippy::too_many_arguments)]
     pub fn add_item(&self, rect: &Space) {
         let (mut x, mut y, mut z) = rect.center();
         x -= f64::from(self.bin_spec.width) * 0.5;
@@ -56,9 +56,9 @@ pub struct Item {
     pub group: usize,
 }
 
-impl Into<Cuboid> for &Item {
-    fn into(self) -> Cuboid {
-        Cuboid::new(self.width, self.depth, self.height)
+impl From<&Item> for Cuboid {
+    fn from(item: &Item) -> Self {
+        Cuboid::new(item.width, item.depth, item.height)
     }
 }
 
@@ -26,18 +26,18 @@ use yew::virtual_dom::VNode;
 use super::three::ThreeRender;
 use super::types::ProblemSpec;
 use kaosu_packer::geom::Cuboid;
-use kaosu_packer::{PackSolution, Params};
+use kaosu_packer::{PackReport, Params};
 
 #[derive(PartialEq, Clone)]
 pub struct Props {
-    pub solution: Rc<RefCell<PackSolution>>,
+    pub report: Rc<RefCell<PackReport>>,
     pub problem_spec: Rc<RefCell<ProblemSpec>>,
 }
 
 impl Default for Props {
     fn default() -> Self {
         Props {
-            solution: Rc::default(),
+            report: Rc::default(),
             problem_spec: Rc::new(RefCell::new(ProblemSpec {
                 params: Params::default(),
                 bin: Cuboid::new(0, 0, 0),
@@ -53,9 +53,8 @@ pub enum Msg {
 }
 
 pub struct Visualize {
-    solution: Rc<RefCell<PackSolution>>,
+    report: Rc<RefCell<PackReport>>,
     problem_spec: Rc<RefCell<ProblemSpec>>,
-    utilization: Vec<f64>,
     current_idx: usize,
     canvas: Element,
     render: ThreeRender,
@@ -66,17 +65,15 @@ impl Component for Visualize {
     type Properties = Props;
 
     fn create(props: Self::Properties, _: ComponentLink<Self>) -> Self {
-        let (solution, problem_spec) = (props.solution, props.problem_spec);
+        let (report, problem_spec) = (props.report, props.problem_spec);
         let bin_spec = problem_spec.borrow().bin;
-        let utilization = Self::cal_utilization(solution.borrow().as_ref(), &bin_spec);
         let canvas = Self::create_canvas(480, 800);
         let render = ThreeRender::new(canvas.clone(), bin_spec);
 
         Visualize {
             current_idx: 0,
-            solution,
+            report,
             problem_spec,
-            utilization,
             canvas,
             render,
         }
@@ -93,7 +90,7 @@ impl Component for Visualize {
                 }
             }
             Msg::NextBin => {
-                if self.current_idx == self.utilization.len() - 1 {
+                if self.current_idx == self.report.borrow().bins.len() - 1 {
                     false
                 } else {
                     self.current_idx += 1;
@@ -130,10 +127,10 @@ impl Visualize {
                     {"Prev Bin"}
                 </button>
                 <span>
-                    {format!("Bin: {} / {}", self.current_idx + 1, self.solution.borrow().len())}
+                    {format!("Bin: {} / {}", self.current_idx + 1, self.report.borrow().bins.len())}
                 </span>
                 <span>
-                    {format!("Utilization: {:.2}%", self.utilization[self.current_idx])}
+                    {format!("Utilization: {:.2}%", self.report.borrow().utilization[self.current_idx] * 100.)}
                 </span>
                 <button class="pure-button pure-button-primary",
                         onclick=|_| Msg::NextBin,>
@@ -144,7 +141,7 @@ impl Visualize {
     }
 
     fn view_render_table(&self) -> Html<Self> {
-        let solution = &self.solution.borrow()[self.current_idx];
+        let solution = &self.report.borrow().bins[self.current_idx];
         html! {
             <div class="table-wrapper",>
                 <table class="pure-table",>
@@ -179,26 +176,12 @@ impl Visualize {
 
     fn render_items(&self) {
         self.render.clear();
-        let solution = self.solution.borrow();
-        for p in solution[self.current_idx].iter() {
+        let report = self.report.borrow();
+        for p in report.bins[self.current_idx].iter() {
             self.render.add_item(&p.space);
         }
     }
 
-    fn cal_utilization(solution: &PackSolution, bin_spec: &Cuboid) -> Vec<f64> {
-        let bin_vol = bin_spec.volume();
-        solution
-            .iter()
-            .map(|items| {
-                let vol_used: i32 = items
-                    .iter()
-                    .map(|i| i.space.width() * i.space.height() * i.space.depth())
-                    .sum();
-                (f64::from(vol_used) / f64::from(bin_vol)) * 100.0
-            })
-            .collect()
-    }
-
     fn create_canvas(height: u32, width: u32) -> Element {
         let el = stdweb::web::document().create_element("canvas").unwrap();
         let canvas: CanvasElement = el.clone().try_into().unwrap();